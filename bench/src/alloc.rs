@@ -0,0 +1,90 @@
+//! Global allocator wrapper that tracks current and peak heap usage.
+//!
+//! Install it in a binary or bench target with:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: bench::alloc::TrackingAllocator = bench::alloc::TrackingAllocator;
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    #[inline]
+    fn grow(size: usize) {
+        let current = CURRENT.fetch_add(size, Relaxed) + size;
+        if current > PEAK.load(Relaxed) {
+            PEAK.fetch_max(current, Relaxed);
+        }
+    }
+
+    #[inline]
+    fn shrink(size: usize) {
+        CURRENT.fetch_sub(size, Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                Self::grow(new_size - layout.size());
+            } else {
+                Self::shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Returns whether [`TrackingAllocator`] is installed as the global allocator.
+pub fn is_tracking() -> bool {
+    PEAK.load(Relaxed) > 0
+}
+
+/// Returns the number of bytes currently allocated.
+pub fn current() -> usize {
+    CURRENT.load(Relaxed)
+}
+
+/// Returns the peak number of bytes allocated since the last [`reset_peak`].
+pub fn peak() -> usize {
+    PEAK.load(Relaxed)
+}
+
+/// Resets the peak to the current number of bytes allocated and returns it.
+pub fn reset_peak() -> usize {
+    let current = current();
+    PEAK.store(current, Relaxed);
+    current
+}
@@ -0,0 +1,82 @@
+//! Interleaves samples of backends each served by a child process, see [`bench::interleave`].
+
+use bench::{
+    interleave::{interleave, Contender, Process},
+    suite::build,
+    BenchConfig,
+};
+use clap::Parser;
+use std::{collections::BTreeMap, fs, path::PathBuf, process::Command, time::Duration};
+
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Contenders as `<package>:<hash>[@<config>]`, each compared to the first.
+    #[arg(required = true, num_args = 2..)]
+    contenders: Vec<String>,
+    #[arg(long)]
+    log_permutations: usize,
+    /// Minimum number of rounds.
+    #[arg(long, default_value_t = 10)]
+    sample_size: usize,
+    /// Maximum number of rounds.
+    #[arg(long, default_value_t = 100)]
+    max_sample_size: usize,
+    /// Minimum number of warm-up rounds.
+    #[arg(long, default_value_t = 1)]
+    warm_up_iters: usize,
+    /// Minimum warm-up time in seconds.
+    #[arg(long, default_value_t = 3.0)]
+    warm_up_time: f64,
+    /// Target width of the 95% confidence interval of every median time, relative to the median.
+    #[arg(long, default_value_t = 0.02)]
+    target_ci: f64,
+    /// Time budget of sampling in seconds.
+    #[arg(long, default_value_t = 60.0)]
+    time_budget: f64,
+    /// Writes the report as JSON to the given path.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut exes = BTreeMap::new();
+    let mut contenders = Vec::<Box<dyn Contender>>::new();
+    for contender in &args.contenders {
+        let Some((package, hash)) = contender.split_once(':') else {
+            panic!("invalid contender `{contender}`, expected `<package>:<hash>`");
+        };
+        if !exes.contains_key(package) {
+            eprintln!("building {package}");
+            exes.insert(package, build(package.as_ref()).unwrap());
+        }
+        let mut command = Command::new(&exes[package]);
+        command
+            .args(["--hash", hash])
+            .args(["--log-permutations", &args.log_permutations.to_string()])
+            .current_dir(package);
+        contenders.push(Box::new(
+            Process::spawn(contender.clone(), command).unwrap(),
+        ));
+    }
+
+    let config = BenchConfig {
+        warm_up_iters: args.warm_up_iters,
+        warm_up_time: Duration::from_secs_f64(args.warm_up_time),
+        min_samples: args.sample_size,
+        max_samples: args.max_sample_size.max(args.sample_size),
+        target_ci: args.target_ci,
+        time_budget: Duration::from_secs_f64(args.time_budget),
+        timeline_interval: None,
+    };
+    let report = interleave(&mut contenders, &config).unwrap();
+    println!("{report}");
+    if let Some(output) = args.output {
+        fs::write(output, serde_json::to_vec_pretty(&report).unwrap()).unwrap();
+    }
+    if !report.verify_failures.is_empty() {
+        std::process::exit(1);
+    }
+}
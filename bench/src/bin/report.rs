@@ -0,0 +1,89 @@
+//! Renders Markdown tables from JSON results written by `--output` of each backend.
+
+use bench::report::{
+    find, load,
+    markdown::{fits, pareto, pivot, table, Column, Filter},
+    svg::charts,
+};
+use clap::Parser;
+use std::{fs, path::PathBuf};
+
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Result files, defaults to `<package>/report/*.json` under the current directory.
+    paths: Vec<PathBuf>,
+    /// Only include results with the given number of threads.
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Only include results with the given log inverse rate.
+    #[arg(long)]
+    rate: Option<usize>,
+    /// Render a table per permutation with backends side by side, showing the given column.
+    #[arg(long, value_enum)]
+    pivot: Option<Column>,
+    /// Show the speedup in proving time over the given backend, e.g. `plonky3`.
+    #[arg(long)]
+    reference: Option<String>,
+    /// Write SVG charts to the given directory instead, and print Markdown links to them.
+    #[arg(long, conflicts_with_all = ["pivot", "reference"])]
+    svg: Option<PathBuf>,
+    /// Render the PCS configurations on the Pareto front of proving time, verifying time and
+    /// proof size instead, e.g. of a suite sweeping rates and grinding bits.
+    #[arg(long, conflicts_with_all = ["pivot", "reference", "rate", "svg"])]
+    pareto: bool,
+    /// Render fits of proving time, peak memory and proof size against the number of
+    /// permutations instead, with predictions at larger sizes and sizes deviating from the fit.
+    #[arg(long, conflicts_with_all = ["pivot", "reference", "svg", "pareto"])]
+    fit: bool,
+    /// Log number of permutations to extrapolate the fits to.
+    #[arg(long, value_delimiter = ',', default_values_t = [24, 28], requires = "fit")]
+    extrapolate: Vec<usize>,
+    /// Relative deviation from the fit above which a size is flagged.
+    #[arg(long, default_value_t = 0.1, requires = "fit")]
+    deviation: f64,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let paths = if args.paths.is_empty() {
+        find(".").unwrap()
+    } else {
+        args.paths
+    };
+    let records = load(paths).unwrap();
+    let filter = Filter {
+        num_threads: args.threads,
+        log_inv_rate: args.rate,
+    };
+
+    if let Some(dir) = args.svg {
+        fs::create_dir_all(&dir).unwrap();
+        for chart in charts(&records, &filter) {
+            let path = dir.join(chart.file_name());
+            fs::write(&path, &chart.svg).unwrap();
+            println!("![{}]({})", chart.file_name(), path.display());
+        }
+        return;
+    }
+
+    if args.fit {
+        print!(
+            "{}",
+            fits(&records, &filter, &args.extrapolate, args.deviation)
+        );
+        return;
+    }
+
+    if args.pareto {
+        print!("{}", pareto(&records, &filter));
+        return;
+    }
+
+    let out = match args.pivot {
+        Some(column) => pivot(&records, &filter, column, args.reference.as_deref()),
+        None => table(&records, &filter, args.reference.as_deref()),
+    };
+    print!("{out}");
+}
@@ -0,0 +1,117 @@
+//! Runs a suite of benchmarks across backend packages described by a manifest, see
+//! [`bench::suite`].
+
+use bench::suite::{build, eta, run, Manifest, Outcome};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Suite manifest, package directories are relative to it.
+    #[arg(default_value = "suite.toml")]
+    manifest: PathBuf,
+    /// Only run packages with the given names.
+    #[arg(long, value_delimiter = ',')]
+    only: Vec<String>,
+    /// Print the runs without running them.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let manifest = Manifest::load(&args.manifest).unwrap_or_else(|err| {
+        Args::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!("invalid manifest {}: {err}", args.manifest.display()),
+            )
+            .exit()
+    });
+    let root = args.manifest.parent().unwrap_or(Path::new("."));
+    let jobs = manifest
+        .jobs()
+        .into_iter()
+        .filter(|job| args.only.is_empty() || args.only.contains(&job.package))
+        .collect::<Vec<_>>();
+
+    if args.dry_run {
+        jobs.iter().for_each(|job| println!("{job}"));
+        return ExitCode::SUCCESS;
+    }
+
+    let mut exes = BTreeMap::new();
+    for job in &jobs {
+        if !exes.contains_key(&job.package) {
+            eprintln!("building {}", job.package);
+            exes.insert(job.package.clone(), build(&root.join(&job.package)));
+        }
+    }
+
+    let output = root.join(&manifest.output);
+    let mut records = Vec::new();
+    let mut failures = Vec::new();
+    let mut elapsed = Vec::new();
+    for (idx, job) in jobs.iter().enumerate() {
+        let start = Instant::now();
+        let outcome = match &exes[&job.package] {
+            Ok(exe) => run(root, exe, job),
+            Err(err) => Outcome::Error(std::io::Error::other(err.to_string())),
+        };
+        elapsed.push(start.elapsed());
+
+        let eta = eta(&elapsed, jobs.len() - idx - 1)
+            .map(|eta| format!(", eta {}", hms(eta)))
+            .unwrap_or_default();
+        eprintln!(
+            "[{}/{}] {job}: {outcome} in {}{eta}",
+            idx + 1,
+            jobs.len(),
+            hms(start.elapsed()),
+        );
+
+        match outcome {
+            Outcome::Done(record) => {
+                records.push(*record);
+                fs::write(&output, serde_json::to_vec_pretty(&records).unwrap()).unwrap();
+            }
+            outcome => failures.push((job, outcome)),
+        }
+    }
+
+    eprintln!(
+        "{} of {} runs written to {}",
+        records.len(),
+        jobs.len(),
+        output.display()
+    );
+    if failures.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    eprintln!("failures:");
+    for (job, outcome) in failures {
+        eprintln!(
+            "  {job}: {outcome}, see {}/report/{}.log",
+            job.package,
+            job.name()
+        );
+    }
+    ExitCode::FAILURE
+}
+
+fn hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m{s:02}s"),
+        (h, m, s) => format!("{h}h{m:02}m{s:02}s"),
+    }
+}
@@ -0,0 +1,302 @@
+//! Proving of batches too large for a single proof, split into chunks proven by concurrent
+//! workers within a memory budget.
+//!
+//! Each worker owns an instance of the chunk size, so instances don't need to be shared across
+//! threads, and the peak heap of one worker (setup and a proof) decides how many fit in the budget.
+
+use crate::{
+    alloc, check,
+    hash::HashDescriptor,
+    input::InputSource,
+    report::Description,
+    util::{human_size, human_throughput, human_time, peak_rss},
+    HashInSnark,
+};
+use core::fmt::{self, Display};
+use rayon::current_num_threads;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Barrier,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug)]
+pub struct ChunkedConfig {
+    /// Bytes of heap the concurrent workers may allocate in total.
+    pub memory_budget: usize,
+    /// Log of permutations per chunk, selected by maximum throughput of trials if not given.
+    pub chunk_log_permutations: Option<usize>,
+    /// Smallest log of permutations per chunk to try.
+    pub min_chunk_log_permutations: usize,
+    /// Maximum number of concurrent workers, defaults to the number of rayon threads.
+    pub max_concurrency: Option<usize>,
+}
+
+impl Default for ChunkedConfig {
+    fn default() -> Self {
+        Self {
+            memory_budget: 8 << 30,
+            chunk_log_permutations: None,
+            min_chunk_log_permutations: 10,
+            max_concurrency: None,
+        }
+    }
+}
+
+/// Trial of a chunk size.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChunkTrial {
+    pub chunk_permutations: usize,
+    /// Peak heap of a single worker, if the allocator is tracked.
+    pub peak_heap: Option<usize>,
+    /// Number of workers fitting in the memory budget, zero if even one doesn't.
+    pub concurrency: usize,
+    /// Permutations per second of all workers proving a chunk each.
+    pub throughput: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChunkedReport {
+    /// Name of the permutation, i.e. `HashDescriptor::NAME`.
+    pub permutation: String,
+    pub description: Description,
+    /// Permutations proven in total, which is rounded up to whole chunks.
+    pub num_permutations: usize,
+    pub chunk_permutations: usize,
+    pub num_chunks: usize,
+    pub concurrency: usize,
+    pub memory_budget: usize,
+    /// Wall time of proving all chunks.
+    pub time: Duration,
+    /// Permutations per second of all chunks.
+    pub throughput: f64,
+    /// Total bytes of serialized proofs.
+    pub proof_size: usize,
+    /// Total time of verifying all deserialized proofs on a single worker.
+    pub verify_time: Duration,
+    /// Errors of the proofs of chunks failing to verify.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify_failures: Vec<String>,
+    /// Peak heap allocated while proving all chunks.
+    pub peak_heap: Option<usize>,
+    /// Peak resident set size of the process.
+    pub peak_rss: Option<usize>,
+    /// Trials of chunk sizes, empty if the chunk size was given.
+    pub trials: Vec<ChunkTrial>,
+}
+
+impl Display for ChunkedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "    system: {}", self.description)?;
+        for trial in &self.trials {
+            write!(f, "     trial: 2^{}", trial.chunk_permutations.ilog2())?;
+            if let Some(peak_heap) = trial.peak_heap {
+                write!(f, ", heap {}", human_size(peak_heap as f64))?;
+            }
+            match trial.throughput {
+                Some(throughput) => writeln!(
+                    f,
+                    ", {} workers, {}",
+                    trial.concurrency,
+                    human_throughput(throughput)
+                )?,
+                None => writeln!(f, ", over budget")?,
+            }
+        }
+        writeln!(
+            f,
+            "    chunks: {} of {} permutations, {} concurrently",
+            self.num_chunks, self.chunk_permutations, self.concurrency
+        )?;
+        writeln!(f, "      time: {}", human_time(self.time))?;
+        writeln!(f, "throughput: {}", human_throughput(self.throughput))?;
+        writeln!(f, "proof size: {}", human_size(self.proof_size as f64))?;
+        writeln!(f, "    verify: {}", human_time(self.verify_time))?;
+        if let Some(peak_heap) = self.peak_heap {
+            writeln!(f, " peak heap: {}", human_size(peak_heap as f64))?;
+        }
+        write!(f, "    budget: {}", human_size(self.memory_budget as f64))?;
+        if let Some(peak_rss) = self.peak_rss {
+            write!(f, "\n  peak mem: {}", human_size(peak_rss as f64))?;
+        }
+        if let Some(err) = self.verify_failures.first() {
+            write!(
+                f,
+                "\n   invalid: {} of {} chunks failed to verify, first with {err}",
+                self.verify_failures.len(),
+                self.num_chunks,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Proves `num_permutations` in chunks, selecting the chunk size first unless given.
+pub fn chunked<H: HashInSnark>(num_permutations: usize, config: &ChunkedConfig) -> ChunkedReport {
+    let max_concurrency = config.max_concurrency.unwrap_or_else(current_num_threads);
+    let log_permutations = num_permutations.next_power_of_two().ilog2() as usize;
+
+    let (trials, chunk_log_permutations) = match config.chunk_log_permutations {
+        Some(chunk_log_permutations) => (Vec::new(), chunk_log_permutations),
+        None => {
+            let mut trials = Vec::new();
+            let mut best = None;
+            for chunk_log_permutations in
+                config.min_chunk_log_permutations.min(log_permutations)..=log_permutations
+            {
+                let trial = trial::<H>(1 << chunk_log_permutations, config, max_concurrency);
+                eprintln!(
+                    "chunk 2^{chunk_log_permutations}: {}",
+                    trial
+                        .throughput
+                        .map_or("over budget".to_string(), human_throughput)
+                );
+                let Some(throughput) = trial.throughput else {
+                    // Larger chunks only take more memory.
+                    trials.push(trial);
+                    break;
+                };
+                if best.is_none_or(|(_, best)| throughput > best) {
+                    best = Some((chunk_log_permutations, throughput));
+                }
+                trials.push(trial);
+            }
+            let (chunk_log_permutations, _) =
+                best.expect("smallest chunk doesn't fit in the memory budget");
+            (trials, chunk_log_permutations)
+        }
+    };
+
+    let concurrency = match trials
+        .iter()
+        .find(|trial| trial.chunk_permutations == 1 << chunk_log_permutations)
+    {
+        Some(trial) => trial.concurrency,
+        None => {
+            let (_, peak_heap) = footprint::<H>(1 << chunk_log_permutations);
+            fit(peak_heap, config, max_concurrency)
+        }
+    };
+    let concurrency = concurrency
+        .min(num_permutations >> chunk_log_permutations)
+        .max(1);
+
+    let baseline = alloc::reset_peak();
+    let (time, proofs) =
+        prove_concurrently::<H>(1 << chunk_log_permutations, concurrency, num_permutations);
+    let peak_heap = alloc::is_tracking().then(|| alloc::peak().saturating_sub(baseline));
+
+    let snark = H::new(1 << chunk_log_permutations);
+    let start = Instant::now();
+    let verify_failures = proofs
+        .iter()
+        .filter_map(|bytes| check(&snark, bytes).err())
+        .collect();
+    let verify_time = start.elapsed();
+
+    let chunk_permutations = snark.num_permutations();
+    let num_chunks = proofs.len();
+    let num_permutations = num_chunks * chunk_permutations;
+    ChunkedReport {
+        permutation: H::Hash::NAME.to_string(),
+        description: snark.describe(),
+        num_permutations,
+        chunk_permutations,
+        num_chunks,
+        concurrency,
+        memory_budget: config.memory_budget,
+        time,
+        throughput: num_permutations as f64 / time.as_secs_f64(),
+        proof_size: proofs.iter().map(Vec::len).sum(),
+        verify_time,
+        verify_failures,
+        peak_heap,
+        peak_rss: peak_rss(),
+        trials,
+    }
+}
+
+/// Measures the peak heap of a worker, then the throughput of as many workers as fit in the
+/// budget proving a chunk each.
+fn trial<H: HashInSnark>(
+    chunk_permutations: usize,
+    config: &ChunkedConfig,
+    max_concurrency: usize,
+) -> ChunkTrial {
+    let (num_permutations, peak_heap) = footprint::<H>(chunk_permutations);
+    let concurrency = fit(peak_heap, config, max_concurrency);
+    let throughput = (concurrency > 0).then(|| {
+        let (time, proofs) = prove_concurrently::<H>(
+            chunk_permutations,
+            concurrency,
+            concurrency * chunk_permutations,
+        );
+        (num_permutations * proofs.len()) as f64 / time.as_secs_f64()
+    });
+    ChunkTrial {
+        chunk_permutations,
+        peak_heap,
+        concurrency,
+        throughput,
+    }
+}
+
+/// Sets up and proves a chunk, returning its actual number of permutations and the peak heap, if
+/// the allocator is tracked.
+fn footprint<H: HashInSnark>(chunk_permutations: usize) -> (usize, Option<usize>) {
+    let baseline = alloc::reset_peak();
+    let snark = H::new(chunk_permutations);
+    drop(snark.prove(snark.generate_input(InputSource::new())));
+    let num_permutations = snark.num_permutations();
+    drop(snark);
+    let peak_heap = alloc::is_tracking().then(|| alloc::peak().saturating_sub(baseline));
+    (num_permutations, peak_heap)
+}
+
+/// Returns the number of workers with `peak_heap` fitting in the budget, or a single worker if
+/// the heap is not tracked.
+fn fit(peak_heap: Option<usize>, config: &ChunkedConfig, max_concurrency: usize) -> usize {
+    match peak_heap {
+        Some(peak_heap) => (config.memory_budget / peak_heap.max(1)).min(max_concurrency),
+        None => 1,
+    }
+}
+
+/// Proves at least `num_permutations` in chunks by `concurrency` workers, returning the wall time
+/// of proving after all workers are set up, and the serialized proofs.
+fn prove_concurrently<H: HashInSnark>(
+    chunk_permutations: usize,
+    concurrency: usize,
+    num_permutations: usize,
+) -> (Duration, Vec<Vec<u8>>) {
+    let next = AtomicUsize::new(0);
+    let barrier = Barrier::new(concurrency + 1);
+    thread::scope(|scope| {
+        let workers = (0..concurrency)
+            .map(|_| {
+                scope.spawn(|| {
+                    let snark = H::new(chunk_permutations);
+                    let mut rng = InputSource::new();
+                    let mut proofs = Vec::new();
+                    barrier.wait();
+                    while next.fetch_add(snark.num_permutations(), Relaxed) < num_permutations {
+                        let proof = snark.prove(snark.generate_input(&mut rng));
+                        proofs.push(H::serialize_proof(&proof));
+                    }
+                    proofs
+                })
+            })
+            .collect::<Vec<_>>();
+        barrier.wait();
+        let start = Instant::now();
+        let proofs = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect();
+        (start.elapsed(), proofs)
+    })
+}
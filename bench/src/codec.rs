@@ -0,0 +1,148 @@
+//! Encodings of proofs, to measure how much of the proof size is overhead of the encoding rather
+//! than field elements and hashes.
+
+use crate::{
+    hash::HashDescriptor,
+    input::InputSource,
+    report::Description,
+    stats,
+    util::{human_size, human_time},
+    HashInSnark,
+};
+use bincode::Options;
+use core::{
+    fmt::{self, Display},
+    hint::black_box,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+pub mod packed;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// `bincode::serialize`, with integers and lengths in fixed width words.
+    Bincode,
+    /// bincode with integers and lengths in varints.
+    Varint,
+    /// [`packed`] encoding with field elements in `bits` bits.
+    Packed { bits: u32 },
+}
+
+impl Codec {
+    /// Returns all codecs, packing field elements of `field_bits` bits.
+    pub fn all(field_bits: u32) -> [Self; 3] {
+        [
+            Self::Bincode,
+            Self::Varint,
+            Self::Packed { bits: field_bits },
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bincode => "bincode",
+            Self::Varint => "varint",
+            Self::Packed { .. } => "packed",
+        }
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Vec<u8> {
+        match self {
+            Self::Bincode => bincode::serialize(value).unwrap(),
+            Self::Varint => bincode::DefaultOptions::new().serialize(value).unwrap(),
+            Self::Packed { bits } => packed::encode(value, bits).unwrap(),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> T {
+        match self {
+            Self::Bincode => bincode::deserialize(bytes).unwrap(),
+            Self::Varint => bincode::DefaultOptions::new().deserialize(bytes).unwrap(),
+            Self::Packed { bits } => packed::decode(bytes, bits).unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CodecStats {
+    pub codec: String,
+    pub size: usize,
+    /// Median time of encoding a proof.
+    pub encode_time: Duration,
+    /// Median time of decoding a proof.
+    pub decode_time: Duration,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CodecReport {
+    /// Name of the permutation, i.e. `HashDescriptor::NAME`.
+    pub permutation: String,
+    pub description: Description,
+    pub num_permutations: usize,
+    pub field_bits: u32,
+    pub codecs: Vec<CodecStats>,
+}
+
+impl Display for CodecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "    system: {}", self.description)?;
+        let smallest = self.codecs.iter().map(|c| c.size).min().unwrap_or_default();
+        write!(f, "     field: {} bits", self.field_bits)?;
+        for codec in &self.codecs {
+            write!(
+                f,
+                "\n{:>10}: {} ({:+.2}%), encode {}, decode {}",
+                codec.codec,
+                human_size(codec.size as f64),
+                (codec.size as f64 / smallest as f64 - 1.0) * 100.0,
+                human_time(codec.encode_time),
+                human_time(codec.decode_time),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn median_time(num_samples: usize, mut f: impl FnMut()) -> Duration {
+    let samples = (0..num_samples)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64()
+        })
+        .collect::<Vec<_>>();
+    Duration::from_secs_f64(stats::median(&samples))
+}
+
+/// Encodes and decodes a proof by each codec `num_samples` times, verifying the decoded proofs.
+pub fn codecs<H: HashInSnark>(num_permutations: usize, num_samples: usize) -> CodecReport {
+    let snark = H::new(num_permutations);
+    let proof = snark.prove(snark.generate_input(InputSource::new()));
+
+    let codecs = Codec::all(H::field_bits())
+        .into_iter()
+        .map(|codec| {
+            let bytes = H::encode_proof(&proof, codec);
+            snark.verify(&H::decode_proof(&bytes, codec)).unwrap();
+            CodecStats {
+                codec: codec.name().to_string(),
+                size: bytes.len(),
+                encode_time: median_time(num_samples, || {
+                    drop(black_box(H::encode_proof(&proof, codec)))
+                }),
+                decode_time: median_time(num_samples, || {
+                    drop(black_box(H::decode_proof(&bytes, codec)))
+                }),
+            }
+        })
+        .collect();
+
+    CodecReport {
+        permutation: H::Hash::NAME.to_string(),
+        description: snark.describe(),
+        num_permutations: snark.num_permutations(),
+        field_bits: H::field_bits(),
+        codecs,
+    }
+}
@@ -0,0 +1,804 @@
+//! Bit-packed encoding of the serde data model, like bincode but writing unsigned integers of the
+//! smallest word holding a field element in the bit width of the field, lengths and variant indices
+//! as LEB128 varints, and booleans and option tags as single bits.
+//!
+//! A packed integer not below `2^bits - 1` is written as all ones followed by the full word, so
+//! `bits` must be such that all ones isn't a canonical element, e.g. 31 for BabyBear or M31.
+
+use core::fmt::{self, Display};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor},
+    ser::{self, Serialize},
+};
+
+#[derive(Debug)]
+pub struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+fn mask(n: u32) -> u64 {
+    u64::MAX.checked_shr(64 - n).unwrap_or(0)
+}
+
+/// Returns the width of the integers packed in `bits`.
+fn packed_width(bits: u32) -> u32 {
+    bits.next_power_of_two().max(8)
+}
+
+/// Encodes `value` with field elements in `bits` bits.
+pub fn encode<T: Serialize + ?Sized>(value: &T, bits: u32) -> Result<Vec<u8>> {
+    let mut encoder = Encoder {
+        bytes: Vec::new(),
+        acc: 0,
+        len: 0,
+        bits,
+    };
+    value.serialize(&mut encoder)?;
+    if encoder.len > 0 {
+        encoder.bytes.push(encoder.acc as u8);
+    }
+    Ok(encoder.bytes)
+}
+
+/// Decodes a value encoded by [`encode`] with the same `bits`.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], bits: u32) -> Result<T> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        bits,
+    };
+    let value = T::deserialize(&mut decoder)?;
+    if decoder.pos.div_ceil(8) != bytes.len() {
+        return Err(Error("trailing bytes".to_string()));
+    }
+    Ok(value)
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+    /// Bits not yet written to `bytes`, fewer than 8 between writes.
+    acc: u64,
+    len: u32,
+    bits: u32,
+}
+
+impl Encoder {
+    fn write(&mut self, mut value: u64, mut n: u32) {
+        if self.len == 0 && n == 8 {
+            return self.bytes.push(value as u8);
+        }
+        while n > 0 {
+            let take = n.min(32);
+            self.acc |= (value & mask(take)) << self.len;
+            self.len += take;
+            while self.len >= 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc >>= 8;
+                self.len -= 8;
+            }
+            value = value.checked_shr(take).unwrap_or(0);
+            n -= take;
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.len == 0 {
+            self.bytes.extend_from_slice(bytes);
+        } else {
+            bytes.iter().for_each(|byte| self.write(*byte as u64, 8));
+        }
+    }
+
+    fn write_wide(&mut self, value: u128, width: u32) {
+        self.write(value as u64, width.min(64));
+        if width > 64 {
+            self.write((value >> 64) as u64, width - 64);
+        }
+    }
+
+    fn write_uint(&mut self, value: u128, width: u32) {
+        if width != packed_width(self.bits) || self.bits >= width {
+            return self.write_wide(value, width);
+        }
+        let escape = (1 << self.bits) - 1;
+        if value < escape {
+            self.write_wide(value, self.bits);
+        } else {
+            self.write_wide(escape, self.bits);
+            self.write_wide(value, width);
+        }
+    }
+
+    fn write_varint(&mut self, mut value: u64) {
+        loop {
+            let byte = value & 0x7f;
+            value >>= 7;
+            if value == 0 {
+                return self.write(byte, 8);
+            }
+            self.write(byte | 0x80, 8);
+        }
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<()> {
+        let len = len.ok_or_else(|| Error("length is unknown".to_string()))?;
+        self.write_varint(len as u64);
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.write(v as u64, 1);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_u8(v as u8)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_u16(v as u16)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.serialize_u128(v as u128)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.write_uint(v as u128, 8);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.write_uint(v as u128, 16);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.write_uint(v as u128, 32);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.write_uint(v as u128, 64);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.write_uint(v, 128);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.write(v.to_bits() as u64, 32);
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.write(v.to_bits(), 64);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write(v as u64, 32);
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write_varint(v.len() as u64);
+        self.write_bytes(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.write(0, 1);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        self.write(1, 1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        self.write_varint(variant_index as u64);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_varint(variant_index as u64);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self> {
+        self.write_varint(variant_index as u64);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self> {
+        self.write_varint(variant_index as u64);
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Encoder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct Decoder<'de> {
+    bytes: &'de [u8],
+    /// Position in bits.
+    pos: usize,
+    bits: u32,
+}
+
+impl Decoder<'_> {
+    fn read(&mut self, n: u32) -> Result<u64> {
+        if self.pos % 8 == 0 && n == 8 {
+            let byte = self.bytes.get(self.pos / 8).copied();
+            self.pos += 8;
+            return byte
+                .map(u64::from)
+                .ok_or_else(|| Error("unexpected end of input".to_string()));
+        }
+        let mut value = 0;
+        let mut done = 0;
+        while done < n {
+            let byte = *self
+                .bytes
+                .get(self.pos / 8)
+                .ok_or_else(|| Error("unexpected end of input".to_string()))?;
+            let offset = (self.pos % 8) as u32;
+            let take = (8 - offset).min(n - done);
+            value |= ((byte >> offset) as u64 & mask(take)) << done;
+            done += take;
+            self.pos += take as usize;
+        }
+        Ok(value)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        if self.pos % 8 == 0 {
+            let start = self.pos / 8;
+            let bytes = self
+                .bytes
+                .get(start..start + len)
+                .ok_or_else(|| Error("unexpected end of input".to_string()))?;
+            self.pos += len * 8;
+            return Ok(bytes.to_vec());
+        }
+        (0..len).map(|_| Ok(self.read(8)? as u8)).collect()
+    }
+
+    fn read_wide(&mut self, width: u32) -> Result<u128> {
+        let low = self.read(width.min(64))? as u128;
+        if width > 64 {
+            return Ok(low | (self.read(width - 64)? as u128) << 64);
+        }
+        Ok(low)
+    }
+
+    fn read_uint(&mut self, width: u32) -> Result<u128> {
+        if width != packed_width(self.bits) || self.bits >= width {
+            return self.read_wide(width);
+        }
+        let value = self.read_wide(self.bits)?;
+        if value == (1 << self.bits) - 1 {
+            return self.read_wide(width);
+        }
+        Ok(value)
+    }
+
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read(8)?;
+            value |= (byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error("varint overflows".to_string()))
+    }
+
+    fn read_len(&mut self) -> Result<usize> {
+        Ok(self.read_varint()? as usize)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error("packed encoding is not self-describing".to_string()))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(self.read(1)? == 1)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(self.read_uint(8)? as i8)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i16(self.read_uint(16)? as i16)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(self.read_uint(32)? as i32)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(self.read_uint(64)? as i64)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i128(self.read_uint(128)? as i128)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.read_uint(8)? as u8)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(self.read_uint(16)? as u16)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(self.read_uint(32)? as u32)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(self.read_uint(64)? as u64)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u128(self.read_uint(128)?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(f32::from_bits(self.read(32)? as u32))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(f64::from_bits(self.read(64)?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let c = self.read(32)? as u32;
+        visitor.visit_char(char::from_u32(c).ok_or_else(|| Error(format!("invalid char {c}")))?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        let bytes = self.read_bytes(len)?;
+        visitor.visit_string(String::from_utf8(bytes).map_err(|err| Error(err.to_string()))?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_byte_buf(self.read_bytes(len)?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.read(1)? {
+            0 => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_map(Access { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let len = fields.len();
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error("packed encoding has no identifiers".to_string()))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error("packed encoding is not self-describing".to_string()))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+struct Access<'a, 'de> {
+    de: &'a mut Decoder<'de>,
+    len: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for Access<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Access<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Decoder<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let index = u32::try_from(self.read_varint()?)
+            .map_err(|_| Error("variant index overflows".to_string()))?;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Decoder<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let len = fields.len();
+        visitor.visit_seq(Access { de: self, len })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::packed::{decode, encode};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Opening {
+        Leaf([u32; 4]),
+        Path { siblings: Vec<[u8; 32]>, index: u64 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Proof {
+        commitment: [u32; 8],
+        evals: Vec<u32>,
+        pow_witness: Option<u64>,
+        openings: Vec<Opening>,
+        trusted: bool,
+        label: String,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let proof = Proof {
+            commitment: [0, 1, (1 << 31) - 2, (1 << 31) - 1, 1 << 31, u32::MAX, 7, 8],
+            evals: (0..100).map(|i| i * 12345).collect(),
+            pow_witness: Some(u64::MAX),
+            openings: vec![
+                Opening::Leaf([3, 2, 1, 0]),
+                Opening::Path {
+                    siblings: vec![[0xab; 32]; 3],
+                    index: 5,
+                },
+            ],
+            trusted: true,
+            label: "proof".to_string(),
+        };
+        for bits in [31, 32, 64] {
+            let bytes = encode(&proof, bits).unwrap();
+            assert_eq!(decode::<Proof>(&bytes, bits).unwrap(), proof);
+        }
+        let fixed = encode(&proof, 32).unwrap().len();
+        assert!(encode(&proof, 31).unwrap().len() < fixed);
+        assert!(decode::<Proof>(&encode(&proof, 31).unwrap(), 32).is_err());
+    }
+}
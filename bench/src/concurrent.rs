@@ -0,0 +1,187 @@
+//! Throughput of many small proofs proven at the same time, each by a worker with its own rayon
+//! pool of a few threads, sharing a single instance.
+
+use crate::{
+    hash::HashDescriptor,
+    input::InputSource,
+    report::Description,
+    stats,
+    util::{human_size, human_throughput, human_time, peak_rss},
+    HashInSnark,
+};
+use core::{
+    fmt::{self, Display},
+    hint::black_box,
+};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Barrier,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug)]
+pub struct ConcurrentConfig {
+    /// Number of proofs proven at the same time.
+    pub concurrency: usize,
+    /// Number of threads of the pool of each worker.
+    pub threads_per_proof: usize,
+    /// Number of proofs taken by each worker after a warm-up proof.
+    pub proofs_per_worker: usize,
+}
+
+impl Default for ConcurrentConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            threads_per_proof: 1,
+            proofs_per_worker: 10,
+        }
+    }
+}
+
+/// Quantiles of proving latency.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Latency {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Latency {
+    pub fn new(samples: &[f64]) -> Self {
+        let quantile = |q| Duration::from_secs_f64(stats::quantile(samples, q));
+        Self {
+            p50: quantile(0.5),
+            p90: quantile(0.9),
+            p99: quantile(0.99),
+            max: quantile(1.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConcurrentReport {
+    /// Name of the permutation, i.e. `HashDescriptor::NAME`.
+    pub permutation: String,
+    pub description: Description,
+    /// Permutations of each proof.
+    pub num_permutations: usize,
+    pub concurrency: usize,
+    pub threads_per_proof: usize,
+    pub num_proofs: usize,
+    /// Wall time of all workers proving, after their inputs are generated.
+    pub time: Duration,
+    /// Permutations per second of all workers.
+    pub throughput: f64,
+    /// Latency of each proof.
+    pub latency: Latency,
+    /// Mean size of proofs.
+    pub proof_size: f64,
+    /// Peak resident set size of the process.
+    pub peak_rss: Option<usize>,
+}
+
+impl Display for ConcurrentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "    system: {}", self.description)?;
+        writeln!(
+            f,
+            "   workers: {} of {} threads, {} proofs",
+            self.concurrency, self.threads_per_proof, self.num_proofs
+        )?;
+        writeln!(f, "      time: {}", human_time(self.time))?;
+        writeln!(f, "throughput: {}", human_throughput(self.throughput))?;
+        writeln!(
+            f,
+            "   latency: p50 {}, p90 {}, p99 {}, max {}",
+            human_time(self.latency.p50),
+            human_time(self.latency.p90),
+            human_time(self.latency.p99),
+            human_time(self.latency.max)
+        )?;
+        write!(f, "proof size: {}", human_size(self.proof_size))?;
+        if let Some(peak_rss) = self.peak_rss {
+            write!(f, "\n  peak mem: {}", human_size(peak_rss as f64))?;
+        }
+        Ok(())
+    }
+}
+
+fn pool(num_threads: usize) -> ThreadPool {
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .unwrap()
+}
+
+/// Proves `config.proofs_per_worker` proofs of `num_permutations` by each of `config.concurrency`
+/// workers at the same time.
+///
+/// The instance is set up in a pool of `config.threads_per_proof` threads, so backends sizing
+/// their provers by the number of threads match the pools of the workers.
+pub fn concurrent<H: HashInSnark>(
+    num_permutations: usize,
+    config: &ConcurrentConfig,
+) -> ConcurrentReport {
+    let snark = pool(config.threads_per_proof).install(|| H::new(num_permutations));
+    let barrier = Barrier::new(config.concurrency + 1);
+
+    let (time, samples) = thread::scope(|scope| {
+        let workers = (0..config.concurrency)
+            .map(|_| {
+                scope.spawn(|| {
+                    pool(config.threads_per_proof).install(|| {
+                        let mut rng = InputSource::new();
+                        let proof = snark.prove(snark.generate_input(&mut rng));
+                        snark
+                            .verify(&H::deserialize_proof(&H::serialize_proof(&proof)))
+                            .unwrap();
+                        let inputs = (0..config.proofs_per_worker)
+                            .map(|_| snark.generate_input(&mut rng))
+                            .collect::<Vec<_>>();
+
+                        barrier.wait();
+                        inputs
+                            .into_iter()
+                            .map(|input| {
+                                let start = Instant::now();
+                                let proof = snark.prove(black_box(input));
+                                let elapsed = start.elapsed();
+                                let proof_size = H::serialize_proof(&proof).len();
+                                drop(black_box(proof));
+                                (elapsed.as_secs_f64(), proof_size)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        barrier.wait();
+        let start = Instant::now();
+        let samples = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect::<Vec<_>>();
+        (start.elapsed(), samples)
+    });
+
+    let (latencies, proof_sizes): (Vec<_>, Vec<_>) = samples.into_iter().unzip();
+    let num_permutations = snark.num_permutations();
+    let num_proofs = latencies.len();
+    ConcurrentReport {
+        permutation: H::Hash::NAME.to_string(),
+        description: snark.describe(),
+        num_permutations,
+        concurrency: config.concurrency,
+        threads_per_proof: config.threads_per_proof,
+        num_proofs,
+        time,
+        throughput: (num_proofs * num_permutations) as f64 / time.as_secs_f64(),
+        latency: Latency::new(&latencies),
+        proof_size: proof_sizes.iter().sum::<usize>() as f64 / num_proofs as f64,
+        peak_rss: peak_rss(),
+    }
+}
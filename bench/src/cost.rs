@@ -0,0 +1,153 @@
+//! Counters of hashing done by verifiers, as an estimate of the cost of verifying in a recursive
+//! circuit.
+//!
+//! Backends wrap the Merkle hashers and Fiat-Shamir challengers used by their verifiers to call
+//! [`count_permutations`] and [`count_hash`], which are no-ops outside of [`measure`].
+//!
+//! Field multiplications of verifiers are not counted, as that would take wrapping the field types
+//! the backends are generic over rather than their hashers.
+
+use crate::hash::HashDescriptor;
+use core::marker::PhantomData;
+use digest::{
+    core_api::BlockSizeUser, FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser,
+    Reset, Update,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{
+    AtomicBool, AtomicUsize,
+    Ordering::{Relaxed, SeqCst},
+};
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static PERMUTATIONS: AtomicUsize = AtomicUsize::new(0);
+static HASHES: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierCost {
+    /// Number of permutation calls, either counted or estimated by the hash message length.
+    pub permutations: usize,
+    /// Number of hash and compression calls.
+    pub hashes: usize,
+    /// Number of bytes hashed.
+    pub bytes: usize,
+}
+
+/// Runs `f` with counters enabled and returns the counts, e.g. of a single verification.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, VerifierCost) {
+    let was_counting = COUNTING.swap(true, SeqCst);
+    assert!(!was_counting, "verifier cost is already being measured");
+    PERMUTATIONS.store(0, SeqCst);
+    HASHES.store(0, SeqCst);
+    BYTES.store(0, SeqCst);
+
+    let output = f();
+
+    COUNTING.store(false, SeqCst);
+    let cost = VerifierCost {
+        permutations: PERMUTATIONS.load(SeqCst),
+        hashes: HASHES.load(SeqCst),
+        bytes: BYTES.load(SeqCst),
+    };
+    (output, cost)
+}
+
+#[inline]
+pub fn is_counting() -> bool {
+    COUNTING.load(Relaxed)
+}
+
+/// Counts `n` permutation calls.
+#[inline]
+pub fn count_permutations(n: usize) {
+    if is_counting() {
+        PERMUTATIONS.fetch_add(n, Relaxed);
+    }
+}
+
+/// Counts a hash or compression call of `bytes` bytes, whose permutation calls are counted
+/// separately.
+#[inline]
+pub fn count_hash(bytes: usize) {
+    if is_counting() {
+        HASHES.fetch_add(1, Relaxed);
+        BYTES.fetch_add(bytes, Relaxed);
+    }
+}
+
+/// Counts a hash call of `bytes` bytes, with permutation calls estimated by
+/// [`HashDescriptor::permutations_per_hash`].
+#[inline]
+pub fn count_hash_of<D: HashDescriptor>(bytes: usize) {
+    if is_counting() {
+        count_hash(bytes);
+        count_permutations(D::permutations_per_hash(bytes));
+    }
+}
+
+/// [`digest`] hasher counting each finalized hash with [`count_hash_of`] `D`.
+pub struct CountedDigest<H, D> {
+    inner: H,
+    bytes: usize,
+    _marker: PhantomData<fn() -> D>,
+}
+
+impl<H: Clone, D> Clone for CountedDigest<H, D> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            bytes: self.bytes,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<H: Default, D> Default for CountedDigest<H, D> {
+    fn default() -> Self {
+        Self {
+            inner: H::default(),
+            bytes: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<H: HashMarker, D> HashMarker for CountedDigest<H, D> {}
+
+impl<H: OutputSizeUser, D> OutputSizeUser for CountedDigest<H, D> {
+    type OutputSize = H::OutputSize;
+}
+
+impl<H: BlockSizeUser, D> BlockSizeUser for CountedDigest<H, D> {
+    type BlockSize = H::BlockSize;
+}
+
+impl<H: Update, D> Update for CountedDigest<H, D> {
+    fn update(&mut self, data: &[u8]) {
+        self.bytes += data.len();
+        self.inner.update(data);
+    }
+}
+
+impl<H: FixedOutput, D: HashDescriptor> FixedOutput for CountedDigest<H, D> {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        count_hash_of::<D>(self.bytes);
+        self.inner.finalize_into(out);
+    }
+}
+
+impl<H: Reset, D> Reset for CountedDigest<H, D> {
+    fn reset(&mut self) {
+        self.bytes = 0;
+        self.inner.reset();
+    }
+}
+
+impl<H: FixedOutputReset, D: HashDescriptor> FixedOutputReset for CountedDigest<H, D> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        count_hash_of::<D>(self.bytes);
+        self.bytes = 0;
+        self.inner.finalize_into_reset(out);
+    }
+}
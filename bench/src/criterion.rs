@@ -1,31 +1,260 @@
-use crate::HashInSnark;
-use criterion::{measurement::Measurement, BatchSize, BenchmarkGroup, BenchmarkId, Throughput};
-use rand::{rngs::StdRng, SeedableRng};
+use crate::{alloc, runner::Runner, HashInSnark};
+use criterion::{
+    measurement::{Measurement, ValueFormatter, WallTime},
+    BenchmarkGroup, BenchmarkId, Throughput,
+};
 use rayon::current_num_threads;
+use std::{hint::black_box, time::Instant};
 
-pub fn bench<H: HashInSnark>(
-    group: &mut BenchmarkGroup<impl Measurement>,
+/// [`Measurement`] that can be taken on proof generation and verification.
+pub trait Metric: Measurement {
+    /// Suffix of benchmark ids to keep baselines of different metrics apart.
+    const SUFFIX: &'static str;
+
+    /// Measures `iters` proof generations with inputs given by `input`.
+    fn prove<H: HashInSnark>(snark: &H, iters: u64, input: impl FnMut() -> H::Input)
+        -> Self::Value;
+
+    /// Measures `iters` verifications of `proof`, or returns `None` if the metric doesn't apply
+    /// to verification.
+    fn verify<H: HashInSnark>(snark: &H, iters: u64, proof: &H::Proof) -> Option<Self::Value>;
+}
+
+impl Metric for WallTime {
+    const SUFFIX: &'static str = "";
+
+    fn prove<H: HashInSnark>(
+        snark: &H,
+        iters: u64,
+        mut input: impl FnMut() -> H::Input,
+    ) -> Self::Value {
+        (0..iters)
+            .map(|_| {
+                let input = black_box(input());
+                let start = Instant::now();
+                let proof = snark.prove(input);
+                let elapsed = start.elapsed();
+                drop(black_box(proof));
+                elapsed
+            })
+            .sum()
+    }
+
+    fn verify<H: HashInSnark>(snark: &H, iters: u64, proof: &H::Proof) -> Option<Self::Value> {
+        let start = Instant::now();
+        for _ in 0..iters {
+            snark.verify(black_box(proof)).unwrap();
+        }
+        Some(start.elapsed())
+    }
+}
+
+/// Serialized proof size in bytes.
+///
+/// Only [`Metric::prove`] produces values, and [`Measurement::end`] returns zero.
+pub struct ProofSize;
+
+impl Measurement for ProofSize {
+    type Intermediate = ();
+    type Value = f64;
+
+    fn start(&self) -> Self::Intermediate {}
+
+    fn end(&self, _: Self::Intermediate) -> Self::Value {
+        0.0
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0.0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+}
+
+impl Metric for ProofSize {
+    const SUFFIX: &'static str = "/proof_size";
+
+    // Proof size doesn't vary across iterations, so it's measured once and scaled by `iters`.
+    fn prove<H: HashInSnark>(
+        snark: &H,
+        iters: u64,
+        mut input: impl FnMut() -> H::Input,
+    ) -> Self::Value {
+        H::serialize_proof(&snark.prove(input())).len() as f64 * iters as f64
+    }
+
+    fn verify<H: HashInSnark>(_: &H, _: u64, _: &H::Proof) -> Option<Self::Value> {
+        None
+    }
+}
+
+/// Peak heap allocated in bytes on top of the heap allocated before the call.
+///
+/// Requires [`alloc::TrackingAllocator`] to be installed as the global allocator.
+pub struct PeakHeap;
+
+impl Measurement for PeakHeap {
+    type Intermediate = usize;
+    type Value = f64;
+
+    fn start(&self) -> Self::Intermediate {
+        assert!(
+            alloc::is_tracking(),
+            "PeakHeap requires bench::alloc::TrackingAllocator as global allocator"
+        );
+        alloc::reset_peak()
+    }
+
+    fn end(&self, baseline: Self::Intermediate) -> Self::Value {
+        alloc::peak().saturating_sub(baseline) as f64
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0.0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+}
+
+impl Metric for PeakHeap {
+    const SUFFIX: &'static str = "/peak_heap";
+
+    // Peak doesn't accumulate across iterations, so it's measured once and scaled by `iters`.
+    fn prove<H: HashInSnark>(
+        snark: &H,
+        iters: u64,
+        mut input: impl FnMut() -> H::Input,
+    ) -> Self::Value {
+        let input = input();
+        let baseline = PeakHeap.start();
+        let proof = snark.prove(input);
+        let peak = PeakHeap.end(baseline);
+        drop(black_box(proof));
+        peak * iters as f64
+    }
+
+    fn verify<H: HashInSnark>(snark: &H, iters: u64, proof: &H::Proof) -> Option<Self::Value> {
+        let baseline = PeakHeap.start();
+        snark.verify(proof).unwrap();
+        Some(PeakHeap.end(baseline) * iters as f64)
+    }
+}
+
+struct BytesFormatter;
+
+impl BytesFormatter {
+    fn unit(typical_value: f64) -> (f64, &'static str) {
+        if typical_value < 1024.0 {
+            (1.0, "B")
+        } else if typical_value < 1024.0 * 1024.0 {
+            (1024.0, "KB")
+        } else if typical_value < 1024.0 * 1024.0 * 1024.0 {
+            (1024.0 * 1024.0, "MB")
+        } else {
+            (1024.0 * 1024.0 * 1024.0, "GB")
+        }
+    }
+}
+
+impl ValueFormatter for BytesFormatter {
+    fn scale_values(&self, typical_value: f64, values: &mut [f64]) -> &'static str {
+        let (denominator, unit) = Self::unit(typical_value);
+        values.iter_mut().for_each(|value| *value /= denominator);
+        unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        let (Throughput::Bytes(n) | Throughput::BytesDecimal(n) | Throughput::Elements(n)) =
+            *throughput;
+        let (denominator, _) = Self::unit(typical_value / n as f64);
+        values
+            .iter_mut()
+            .for_each(|value| *value /= n as f64 * denominator);
+        match denominator as usize {
+            1 => "B/perm",
+            1024 => "KB/perm",
+            1048576 => "MB/perm",
+            _ => "GB/perm",
+        }
+    }
+
+    fn scale_for_machines(&self, _: &mut [f64]) -> &'static str {
+        "B"
+    }
+}
+
+fn id<M: Metric>(name: &str, num_permutations: usize) -> BenchmarkId {
+    BenchmarkId::new(
+        format!("{name}{}", M::SUFFIX),
+        format!(
+            "num_threads={}/num_permutations={}",
+            current_num_threads(),
+            num_permutations
+        ),
+    )
+}
+
+pub fn bench<H: HashInSnark, M: Metric>(
+    group: &mut BenchmarkGroup<M>,
     name: impl AsRef<str>,
     num_permutations: impl IntoIterator<Item = usize>,
 ) {
-    let mut rng = StdRng::from_os_rng();
-    for num_permutations in num_permutations {
-        let snark = H::new(num_permutations);
-        let id = BenchmarkId::new(
-            name.as_ref(),
-            format!(
-                "num_threads={}/num_permutations={}",
-                current_num_threads(),
-                snark.num_permutations()
-            ),
-        );
-        group.throughput(Throughput::Elements(snark.num_permutations() as _));
-        group.bench_function(id, |b| {
-            b.iter_batched(
-                || snark.generate_input(&mut rng),
-                |input| snark.prove(input),
-                BatchSize::LargeInput,
+    Runner::<H>::new()
+        .sizes(num_permutations)
+        .instances(|snark, rng| {
+            let id = id::<M>(name.as_ref(), snark.num_permutations());
+            group.throughput(Throughput::Elements(snark.num_permutations() as _));
+            group.bench_function(id, |b| {
+                b.iter_custom(|iters| M::prove(snark, iters, || snark.generate_input(&mut *rng)));
+            });
+        });
+}
+
+pub fn bench_verify<H: HashInSnark, M: Metric>(
+    group: &mut BenchmarkGroup<M>,
+    name: impl AsRef<str>,
+    num_permutations: impl IntoIterator<Item = usize>,
+) {
+    Runner::<H>::new()
+        .sizes(num_permutations)
+        .instances(|snark, rng| {
+            let id = id::<M>(
+                &format!("{}/verify", name.as_ref()),
+                snark.num_permutations(),
             );
+            let proof = snark.prove(snark.generate_input(rng));
+            let proof = H::deserialize_proof(&H::serialize_proof(&proof));
+            if M::verify(snark, 1, &proof).is_none() {
+                return;
+            }
+            group.throughput(Throughput::Elements(snark.num_permutations() as _));
+            group.bench_function(id, |b| {
+                b.iter_custom(|iters| M::verify(snark, iters, &proof).unwrap());
+            });
         });
-    }
 }
@@ -0,0 +1,175 @@
+//! Descriptions of the permutations proven by each backend, used to normalize
+//! throughput across hashes with different state sizes.
+
+/// Collision security in bits targeted by [`HashDescriptor::PERMUTATIONS_PER_COMPRESSION`].
+pub const TARGET_SECURITY_BITS: usize = 128;
+
+pub trait HashDescriptor {
+    const NAME: &'static str;
+
+    /// Bit width of a state word, e.g. 64 for Keccak lanes and 31 for KoalaBear elements.
+    const WORD_BITS: usize;
+
+    /// Number of words in the permutation input.
+    const STATE_WIDTH: usize;
+
+    /// Number of message words absorbed per permutation call.
+    const RATE: usize;
+
+    /// Number of words not overwritten by message, i.e. sponge capacity or chaining value.
+    const CAPACITY: usize;
+
+    /// Number of words in the digest.
+    const DIGEST: usize;
+
+    /// Claimed collision security in bits with the digest of [`Self::DIGEST`] words.
+    const SECURITY_BITS: usize;
+
+    /// Number of permutation calls for a 2-to-1 compression with [`TARGET_SECURITY_BITS`]
+    /// collision security.
+    ///
+    /// Defaults to a sponge absorbing two digests of `2 * TARGET_SECURITY_BITS` bits, with the
+    /// capacity widened to a digest if it is smaller.
+    const PERMUTATIONS_PER_COMPRESSION: usize = {
+        let digest = (2 * TARGET_SECURITY_BITS).div_ceil(Self::WORD_BITS);
+        let capacity = if Self::CAPACITY > digest {
+            Self::CAPACITY
+        } else {
+            digest
+        };
+        (2 * digest).div_ceil(Self::STATE_WIDTH - capacity)
+    };
+
+    /// Number of permutation calls to hash a message of `bytes` bytes.
+    ///
+    /// Defaults to a sponge with `pad10*1` padding, which appends at least 2 bits.
+    fn permutations_per_hash(bytes: usize) -> usize {
+        (8 * bytes + 2).div_ceil(Self::RATE * Self::WORD_BITS)
+    }
+
+    fn state_bytes() -> f64 {
+        (Self::STATE_WIDTH * Self::WORD_BITS) as f64 / 8.0
+    }
+
+    fn rate_bytes() -> f64 {
+        (Self::RATE * Self::WORD_BITS) as f64 / 8.0
+    }
+
+    fn capacity_bytes() -> f64 {
+        (Self::CAPACITY * Self::WORD_BITS) as f64 / 8.0
+    }
+
+    fn digest_bytes() -> f64 {
+        (Self::DIGEST * Self::WORD_BITS) as f64 / 8.0
+    }
+}
+
+/// Keccak-f[1600] as used by Keccak-256.
+pub struct KeccakF1600;
+
+impl HashDescriptor for KeccakF1600 {
+    const NAME: &'static str = "Keccak-f[1600]";
+    const WORD_BITS: usize = 64;
+    const STATE_WIDTH: usize = 25;
+    const RATE: usize = 17;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 4;
+    const SECURITY_BITS: usize = 128;
+}
+
+/// Grøstl-256 permutation `P`.
+pub struct GroestlP;
+
+impl HashDescriptor for GroestlP {
+    const NAME: &'static str = "Grøstl-256 P";
+    const WORD_BITS: usize = 8;
+    const STATE_WIDTH: usize = 64;
+    const RATE: usize = 64;
+    const CAPACITY: usize = 0;
+    const DIGEST: usize = 32;
+    const SECURITY_BITS: usize = 128;
+    // Compression `P(h ^ m) ^ Q(m) ^ h` followed by output transformation `P(x) ^ x`, counting
+    // `Q` as `P`.
+    const PERMUTATIONS_PER_COMPRESSION: usize = 3;
+
+    // Message padded by at least 9 bytes into 64-byte blocks, each compressed by `P` and `Q`,
+    // followed by output transformation.
+    fn permutations_per_hash(bytes: usize) -> usize {
+        2 * (bytes + 9).div_ceil(64) + 1
+    }
+}
+
+/// BLAKE3 compression function, with 8-word chaining value and 16-word message block.
+pub struct Blake3;
+
+impl HashDescriptor for Blake3 {
+    const NAME: &'static str = "BLAKE3";
+    const WORD_BITS: usize = 32;
+    const STATE_WIDTH: usize = 24;
+    const RATE: usize = 16;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 8;
+    const SECURITY_BITS: usize = 128;
+
+    // Message zero-padded into 64-byte blocks, with a single block for the empty message.
+    fn permutations_per_hash(bytes: usize) -> usize {
+        bytes.div_ceil(64).max(1)
+    }
+}
+
+/// BLAKE2s compression function, with 8-word chaining value and 16-word message block.
+pub struct Blake2s;
+
+impl HashDescriptor for Blake2s {
+    const NAME: &'static str = "BLAKE2s";
+    const WORD_BITS: usize = 32;
+    const STATE_WIDTH: usize = 24;
+    const RATE: usize = 16;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 8;
+    const SECURITY_BITS: usize = 128;
+
+    // Message zero-padded into 64-byte blocks, with a single block for the empty message.
+    fn permutations_per_hash(bytes: usize) -> usize {
+        bytes.div_ceil(64).max(1)
+    }
+}
+
+/// Width-16 Poseidon2 over KoalaBear.
+pub struct KoalaBearPoseidon2;
+
+impl HashDescriptor for KoalaBearPoseidon2 {
+    const NAME: &'static str = "Poseidon2 KoalaBear t=16";
+    const WORD_BITS: usize = 31;
+    const STATE_WIDTH: usize = 16;
+    const RATE: usize = 8;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 8;
+    const SECURITY_BITS: usize = 124;
+}
+
+/// Width-16 Poseidon2 over M31.
+pub struct M31Poseidon2;
+
+impl HashDescriptor for M31Poseidon2 {
+    const NAME: &'static str = "Poseidon2 M31 t=16";
+    const WORD_BITS: usize = 31;
+    const STATE_WIDTH: usize = 16;
+    const RATE: usize = 8;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 8;
+    const SECURITY_BITS: usize = 124;
+}
+
+/// Width-16 Poseidon over M31.
+pub struct M31Poseidon;
+
+impl HashDescriptor for M31Poseidon {
+    const NAME: &'static str = "Poseidon M31 t=16";
+    const WORD_BITS: usize = 31;
+    const STATE_WIDTH: usize = 16;
+    const RATE: usize = 8;
+    const CAPACITY: usize = 8;
+    const DIGEST: usize = 8;
+    const SECURITY_BITS: usize = 124;
+}
@@ -0,0 +1,167 @@
+//! Sources of bytes from which backends derive permutation inputs in
+//! [`HashInSnark::generate_input`](crate::HashInSnark::generate_input).
+//!
+//! Backends take any [`RngCore`] and draw states from it the way they would from random bytes, so
+//! real data is fed into the permutations by passing a source reading from a file or stream.
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc, OnceLock,
+    },
+};
+
+/// Bytes installed by [`init`], shared by every [`InputSource::new`] so that sources of
+/// concurrent workers or chunks draw disjoint bytes.
+static BYTES: OnceLock<Arc<Bytes>> = OnceLock::new();
+
+/// Bytes of a file or stream with the offset of the next byte drawn by any source of them.
+pub struct Bytes {
+    bytes: Box<[u8]>,
+    cursor: AtomicUsize,
+}
+
+impl Bytes {
+    /// Returns `bytes` cycled from the start, which must be non-empty.
+    fn new(bytes: impl Into<Box<[u8]>>) -> Self {
+        let bytes = bytes.into();
+        assert!(!bytes.is_empty(), "input source is empty");
+        Self {
+            bytes,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+}
+
+pub enum InputSource {
+    /// Random bytes from the OS.
+    Random(Box<StdRng>),
+    /// Bytes of a file or stream, cycled from the start once exhausted.
+    Bytes(Arc<Bytes>),
+}
+
+impl InputSource {
+    /// Returns a source of the bytes installed by [`init`], otherwise of random bytes.
+    pub fn new() -> Self {
+        match BYTES.get() {
+            Some(bytes) => Self::Bytes(bytes.clone()),
+            None => Self::random(),
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::Random(Box::new(StdRng::from_os_rng()))
+    }
+
+    /// Returns a source of the bytes installed by [`init`], otherwise of random bytes seeded by
+    /// `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        match BYTES.get() {
+            Some(bytes) => Self::Bytes(bytes.clone()),
+            None => Self::Random(Box::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    /// Returns a source of `bytes`, which must be non-empty.
+    pub fn from_bytes(bytes: impl Into<Box<[u8]>>) -> Self {
+        Self::Bytes(Arc::new(Bytes::new(bytes)))
+    }
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for InputSource {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            Self::Random(rng) => rng.fill_bytes(dst),
+            Self::Bytes(shared) => {
+                let bytes = &shared.bytes;
+                let mut pos = shared.cursor.fetch_add(dst.len(), Relaxed) % bytes.len();
+                for dst in dst.chunks_mut(bytes.len()) {
+                    let len = dst.len();
+                    let n = len.min(bytes.len() - pos);
+                    dst[..n].copy_from_slice(&bytes[pos..pos + n]);
+                    dst[n..].copy_from_slice(&bytes[..len - n]);
+                    pos = (pos + len) % bytes.len();
+                }
+            }
+        }
+    }
+}
+
+/// Reads all bytes of `path`, or of stdin if `path` is `-`.
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        fs::read(path)
+    }
+}
+
+/// Installs the bytes of `path` (or stdin if `-`) as the source of all inputs of the harness.
+pub fn init(path: impl AsRef<Path>) -> io::Result<()> {
+    let bytes = read(&path)?;
+    if bytes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("empty input {}", path.as_ref().display()),
+        ));
+    }
+    BYTES
+        .set(Arc::new(Bytes::new(bytes)))
+        .map_err(|_| io::Error::other("input source is already installed"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::{Bytes, InputSource};
+    use rand::RngCore;
+    use std::sync::Arc;
+
+    #[test]
+    fn cycle_bytes() {
+        let mut source = InputSource::from_bytes(vec![1, 2, 3]);
+        let mut dst = [0; 7];
+        source.fill_bytes(&mut dst);
+        assert_eq!(dst, [1, 2, 3, 1, 2, 3, 1]);
+        source.fill_bytes(&mut dst[..2]);
+        assert_eq!(dst[..2], [2, 3]);
+        assert_eq!(source.next_u32(), u32::from_le_bytes([1, 2, 3, 1]));
+    }
+
+    #[test]
+    fn share_bytes() {
+        let bytes = Arc::new(Bytes::new(vec![1, 2, 3, 4, 5]));
+        let mut sources = [InputSource::Bytes(bytes.clone()), InputSource::Bytes(bytes)];
+        let mut dst = [0; 2];
+        sources[0].fill_bytes(&mut dst);
+        assert_eq!(dst, [1, 2]);
+        sources[1].fill_bytes(&mut dst);
+        assert_eq!(dst, [3, 4]);
+        sources[0].fill_bytes(&mut dst);
+        assert_eq!(dst, [5, 1]);
+    }
+}
@@ -0,0 +1,364 @@
+//! Interleaved sampling of several configurations, so that throttling and background load drift
+//! over time affects all of them alike instead of skewing the comparison of runs one after another.
+//!
+//! Configurations of the same binary are set up in the same process by [`Instance`], and ones of
+//! different backends are run by [`Process`] as child processes in `--serve` mode, each proving a
+//! sample whenever asked.
+
+use crate::{
+    check,
+    input::InputSource,
+    report::Description,
+    routine, stats,
+    util::{human_time, pcs_log_inv_rate},
+    BenchConfig, HashInSnark,
+};
+use core::fmt::{self, Display};
+use rayon::current_num_threads;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// p-value below which a difference is reported as significant.
+pub const SIGNIFICANCE: f64 = 0.05;
+
+/// Configuration proving samples on demand.
+pub trait Contender {
+    fn label(&self) -> &str;
+
+    fn num_permutations(&self) -> usize;
+
+    fn describe(&self) -> Description;
+
+    /// Proves a sample, returning the proving time and the error of verifying the proof if it's
+    /// invalid.
+    fn sample(&mut self) -> io::Result<(Duration, Option<String>)>;
+}
+
+/// Instance of `H` in the current process.
+pub struct Instance<H> {
+    label: String,
+    snark: H,
+    rng: InputSource,
+}
+
+impl<H: HashInSnark> Instance<H> {
+    pub fn new(label: String, num_permutations: usize) -> Self {
+        Self {
+            label,
+            snark: H::new(num_permutations),
+            rng: InputSource::new(),
+        }
+    }
+}
+
+/// Returns an [`Instance`] of `H`, e.g. to be picked from a table of configurations.
+pub fn instance<H: HashInSnark + 'static>(
+    label: String,
+    num_permutations: usize,
+) -> Box<dyn Contender> {
+    Box::new(Instance::<H>::new(label, num_permutations))
+}
+
+impl<H: HashInSnark> Contender for Instance<H> {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn num_permutations(&self) -> usize {
+        self.snark.num_permutations()
+    }
+
+    fn describe(&self) -> Description {
+        self.snark.describe()
+    }
+
+    fn sample(&mut self) -> io::Result<(Duration, Option<String>)> {
+        let (time, bytes) = routine(&self.snark, &mut self.rng);
+        Ok((time, check(&self.snark, &bytes).err()))
+    }
+}
+
+/// First message of [`serve`], once set up.
+#[derive(Serialize, Deserialize)]
+struct Ready {
+    num_permutations: usize,
+    description: Description,
+}
+
+/// Message of [`serve`] for each sample.
+#[derive(Serialize, Deserialize)]
+struct Served {
+    time: Duration,
+    failure: Option<String>,
+}
+
+/// Sets up `H` and proves a sample for each line of stdin, writing a JSON line of each to stdout
+/// to be read by [`Process`].
+pub fn serve<H: HashInSnark>(num_permutations: usize) {
+    let mut rng = InputSource::new();
+    let snark = H::new(num_permutations);
+    let ready = Ready {
+        num_permutations: snark.num_permutations(),
+        description: snark.describe(),
+    };
+    println!("{}", serde_json::to_string(&ready).unwrap());
+    for line in io::stdin().lines() {
+        line.unwrap();
+        let (time, bytes) = routine(&snark, &mut rng);
+        let failure = check(&snark, &bytes).err();
+        println!(
+            "{}",
+            serde_json::to_string(&Served { time, failure }).unwrap()
+        );
+    }
+}
+
+/// Child process of a backend binary serving samples by `--serve`, which exits once its stdin is
+/// closed on drop.
+pub struct Process {
+    label: String,
+    ready: Ready,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Process {
+    /// Spawns `command` with `--serve` and waits for it to be set up.
+    pub fn spawn(label: String, mut command: Command) -> io::Result<Self> {
+        let mut child = command
+            .arg("--serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let ready = read(&mut stdout)?;
+        Ok(Self {
+            label,
+            ready,
+            child,
+            stdin,
+            stdout,
+        })
+    }
+}
+
+/// Reads the next JSON line, skipping other output of the backend.
+fn read<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if line.starts_with('{') {
+            return Ok(serde_json::from_str(&line)?);
+        }
+    }
+}
+
+impl Contender for Process {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn num_permutations(&self) -> usize {
+        self.ready.num_permutations
+    }
+
+    fn describe(&self) -> Description {
+        self.ready.description.clone()
+    }
+
+    fn sample(&mut self) -> io::Result<(Duration, Option<String>)> {
+        writeln!(self.stdin.as_mut().unwrap())?;
+        let served = read::<Served>(&mut self.stdout)?;
+        Ok((served.time, served.failure))
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContenderReport {
+    pub label: String,
+    pub description: Description,
+    pub num_permutations: usize,
+    /// Median proving time.
+    pub time: Duration,
+    /// Width of the 95% confidence interval of the median time, relative to the median.
+    pub ci: f64,
+}
+
+/// Comparison of a contender against the first one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Comparison {
+    pub label: String,
+    /// Median of the relative differences of proving time to the first contender in the same
+    /// round.
+    pub difference: f64,
+    /// 95% confidence interval of `difference`, if there are enough rounds.
+    pub difference_ci: Option<(f64, f64)>,
+    /// p-value of the Mann-Whitney U test of both having the same distribution of proving time.
+    pub p_value: f64,
+}
+
+impl Comparison {
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterleaveReport {
+    pub num_threads: usize,
+    pub log_inv_rate: usize,
+    pub contenders: Vec<ContenderReport>,
+    pub comparisons: Vec<Comparison>,
+    /// Number of rounds, each proving a sample of every contender.
+    pub num_rounds: usize,
+    /// Errors of the proofs failing to verify, prefixed by the label of their contender.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify_failures: Vec<String>,
+}
+
+impl Display for InterleaveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, contender) in self.contenders.iter().enumerate() {
+            writeln!(f, "[{idx}] {}", contender.label)?;
+            writeln!(f, "    system: {}", contender.description)?;
+            writeln!(
+                f,
+                "      time: {} ±{:.2}%",
+                human_time(contender.time),
+                contender.ci * 50.0
+            )?;
+            let Some(comparison) = idx.checked_sub(1).map(|idx| &self.comparisons[idx]) else {
+                continue;
+            };
+            write!(f, "    vs [0]: {:+.2}%", comparison.difference * 100.0)?;
+            if let Some((lo, hi)) = comparison.difference_ci {
+                write!(f, " ({:+.2}% to {:+.2}%)", lo * 100.0, hi * 100.0)?;
+            }
+            let p_value = match comparison.p_value {
+                p if p >= 1e-3 => format!("{p:.3}"),
+                p => format!("{p:.1e}"),
+            };
+            writeln!(
+                f,
+                ", p = {p_value}, {}",
+                if comparison.is_significant() {
+                    "significant"
+                } else {
+                    "not significant"
+                }
+            )?;
+        }
+        write!(f, "    rounds: {}", self.num_rounds)?;
+        if let Some(err) = self.verify_failures.first() {
+            write!(
+                f,
+                "\n   invalid: {} proofs failed to verify, first of {err}",
+                self.verify_failures.len(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Warms up and then samples `contenders` in rounds, each proving a sample of every contender in
+/// an order rotated by the round, until the confidence interval of every median is narrow enough,
+/// or sampling runs out of budget.
+pub fn interleave(
+    contenders: &mut [Box<dyn Contender>],
+    config: &BenchConfig,
+) -> io::Result<InterleaveReport> {
+    let mut verify_failures = Vec::new();
+    let mut round = |contenders: &mut [Box<dyn Contender>], rotation: usize| {
+        let k = contenders.len();
+        let mut times = vec![0.0; k];
+        for idx in (0..k).map(|i| (i + rotation) % k) {
+            let (time, failure) = contenders[idx].sample()?;
+            times[idx] = time.as_secs_f64();
+            if let Some(err) = failure {
+                verify_failures.push(format!("{}: {err}", contenders[idx].label()));
+            }
+        }
+        io::Result::Ok(times)
+    };
+
+    let start = Instant::now();
+    let mut iters = 0;
+    while iters < config.warm_up_iters || start.elapsed() < config.warm_up_time {
+        round(contenders, iters)?;
+        iters += 1;
+    }
+
+    let start = Instant::now();
+    let mut rounds = Vec::<Vec<f64>>::new();
+    let samples =
+        |rounds: &[Vec<f64>], idx: usize| -> Vec<f64> { rounds.iter().map(|r| r[idx]).collect() };
+    loop {
+        rounds.push(round(contenders, rounds.len())?);
+
+        let ci = (0..contenders.len())
+            .map(|idx| stats::median_ci_relative_width(&samples(&rounds, idx), stats::Z_95))
+            .fold(0.0, f64::max);
+        if rounds.len() >= config.min_samples
+            && (ci <= config.target_ci
+                || rounds.len() >= config.max_samples
+                || start.elapsed() >= config.time_budget)
+        {
+            break;
+        }
+    }
+
+    let baseline = samples(&rounds, 0);
+    let comparisons = (1..contenders.len())
+        .map(|idx| {
+            let times = samples(&rounds, idx);
+            let differences = (times.iter().zip(&baseline))
+                .map(|(time, baseline)| time / baseline - 1.0)
+                .collect::<Vec<_>>();
+            Comparison {
+                label: contenders[idx].label().to_string(),
+                difference: stats::median(&differences),
+                difference_ci: stats::median_ci(&differences, stats::Z_95),
+                p_value: stats::mann_whitney_u(&baseline, &times),
+            }
+        })
+        .collect();
+    let contenders = contenders
+        .iter()
+        .enumerate()
+        .map(|(idx, contender)| {
+            let times = samples(&rounds, idx);
+            ContenderReport {
+                label: contender.label().to_string(),
+                description: contender.describe(),
+                num_permutations: contender.num_permutations(),
+                time: Duration::from_secs_f64(stats::median(&times)),
+                ci: stats::median_ci_relative_width(&times, stats::Z_95),
+            }
+        })
+        .collect();
+    Ok(InterleaveReport {
+        num_threads: current_num_threads(),
+        log_inv_rate: pcs_log_inv_rate(),
+        contenders,
+        comparisons,
+        num_rounds: rounds.len(),
+        verify_failures,
+    })
+}
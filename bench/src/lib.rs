@@ -1,11 +1,45 @@
+use crate::{
+    codec::Codec,
+    hash::HashDescriptor,
+    input::InputSource,
+    report::{ColdStart, Description, FreshReport, Report},
+    runner::Runner,
+    timeline::{Sampler, Timeline},
+    util::peak_rss,
+    workload::Workload,
+};
 use core::{fmt::Debug, hint::black_box};
-use rand::{rngs::StdRng, RngCore, SeedableRng};
-use std::time::{Duration, Instant};
+use rand::RngCore;
+use std::{
+    env,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
 
+pub mod alloc;
+pub mod chunked;
+pub mod codec;
+pub mod concurrent;
+pub mod cost;
 pub mod criterion;
+pub mod hash;
+pub mod input;
+pub mod interleave;
+pub mod preflight;
+pub mod report;
+pub mod runner;
+pub mod soak;
+pub mod stats;
+pub mod suite;
+pub mod timeline;
+pub mod trace;
 pub mod util;
+pub mod workload;
 
-pub trait HashInSnark {
+/// Proof system proving permutations of a hash, which is set up on and shared by threads of
+/// [`concurrent`] so must be [`Send`] and [`Sync`].
+pub trait HashInSnark: Send + Sync {
+    type Hash: HashDescriptor;
     type Input;
     type Proof;
     type Error: Debug;
@@ -16,18 +50,42 @@ pub trait HashInSnark {
 
     fn num_permutations(&self) -> usize;
 
+    /// Computation proven, with [`Workload::MerkleTree`] taking the number of leaves in
+    /// [`Self::new`] and returning the number of compressions by [`Self::num_permutations`].
+    fn workload(&self) -> Workload {
+        Workload::Permutations
+    }
+
+    /// Whether [`Self::generate_input`] draws the states from `rng`, so they can be derived from
+    /// a file by [`input::init`] rather than generated by the backend itself.
+    const TAKES_INPUT: bool = true;
+
     fn generate_input(&self, rng: impl RngCore) -> Self::Input;
 
     fn prove(&self, input: Self::Input) -> Self::Proof;
 
     fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error>;
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8>;
+    /// Bit width of the field elements of proofs, packed by [`Codec::Packed`].
+    fn field_bits() -> u32;
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8>;
+
+    fn decode_proof(bytes: &[u8], codec: Codec) -> Self::Proof;
+
+    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
+        Self::encode_proof(proof, Codec::Bincode)
+    }
+
+    fn deserialize_proof(bytes: &[u8]) -> Self::Proof {
+        Self::decode_proof(bytes, Codec::Bincode)
+    }
 
-    fn deserialize_proof(bytes: &[u8]) -> Self::Proof;
+    /// Returns the proof system, included in reports.
+    fn describe(&self) -> Description;
 
     fn proof_size(&self) -> usize {
-        let mut rng = StdRng::from_os_rng();
+        let mut rng = InputSource::new();
         let input = self.generate_input(&mut rng);
         let proof = self.prove(input);
         self.verify(&proof).unwrap();
@@ -38,121 +96,526 @@ pub trait HashInSnark {
     }
 }
 
-fn routine<H: HashInSnark>(snark: &H, mut rng: impl RngCore) -> (Duration, usize) {
+/// Proves an input from `rng`, returning the proving time and the serialized proof.
+fn routine<H: HashInSnark>(snark: &H, mut rng: impl RngCore) -> (Duration, Vec<u8>) {
     let input = black_box(snark.generate_input(&mut rng));
 
     let start = Instant::now();
     let proof = snark.prove(input);
     let elapsed = start.elapsed();
 
-    let proof_size = H::serialize_proof(&proof).len();
+    let bytes = H::serialize_proof(&proof);
     drop(black_box(proof));
 
-    (elapsed, proof_size)
+    (elapsed, bytes)
 }
 
-fn warm_up<H: HashInSnark>(snark: &H, mut rng: impl RngCore) {
+/// Verifies the deserialized proof of `bytes`, returning the error if it's invalid.
+fn check<H: HashInSnark>(snark: &H, bytes: &[u8]) -> Result<(), String> {
+    snark
+        .verify(&H::deserialize_proof(bytes))
+        .map_err(|err| format!("{err:?}"))
+}
+
+fn warm_up<H: HashInSnark>(snark: &H, config: &BenchConfig, mut rng: impl RngCore) {
     let start = Instant::now();
-    while Instant::now().duration_since(start).as_secs_f64() < 3.0 {
+    let mut iters = 0;
+    while iters < config.warm_up_iters || start.elapsed() < config.warm_up_time {
         routine(snark, &mut rng);
+        iters += 1;
     }
 }
 
 pub fn run<H: HashInSnark>(num_permutations: usize) {
-    let snark = H::new(num_permutations);
-    let input = black_box(snark.generate_input(StdRng::from_os_rng()));
-    let proof = snark.prove(input);
-    drop(black_box(proof));
+    Runner::<H>::new().sizes([num_permutations]).run();
 }
 
 pub fn test<H: HashInSnark>(num_permutations: usize) -> Result<(), H::Error> {
-    let snark = H::new(num_permutations);
-    let input = snark.generate_input(StdRng::from_os_rng());
-    let proof = snark.prove(input);
-    snark.verify(&proof)?;
-    let bytes = H::serialize_proof(&proof);
-    let proof = H::deserialize_proof(&bytes);
-    snark.verify(&proof)?;
-    Ok(())
+    Runner::<H>::new().sizes([num_permutations]).test()
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    /// Minimum number of warm-up iterations.
+    pub warm_up_iters: usize,
+    /// Minimum warm-up time.
+    pub warm_up_time: Duration,
+    /// Minimum number of samples.
+    pub min_samples: usize,
+    /// Maximum number of samples.
+    pub max_samples: usize,
+    /// Target width of the 95% confidence interval of the median, relative to the median.
+    pub target_ci: f64,
+    /// Time budget of sampling, after which sampling stops once `min_samples` are taken.
+    pub time_budget: Duration,
+    /// Interval of sampling heap and RSS during an extra proof, which is skipped if not given.
+    pub timeline_interval: Option<Duration>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warm_up_iters: 1,
+            warm_up_time: Duration::from_secs(3),
+            min_samples: 10,
+            max_samples: 100,
+            target_ci: 0.02,
+            time_budget: Duration::from_secs(60),
+            timeline_interval: None,
+        }
+    }
 }
 
-pub fn bench<H: HashInSnark>(
+/// Takes samples of `routine` until the confidence interval of the median is narrow enough, or
+/// sampling runs out of budget, returning samples in seconds and the relative interval width.
+fn sample(config: &BenchConfig, mut routine: impl FnMut() -> Duration) -> (Vec<f64>, f64) {
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    loop {
+        samples.push(routine().as_secs_f64());
+
+        let ci = stats::median_ci_relative_width(&samples, stats::Z_95);
+        if samples.len() >= config.min_samples
+            && (ci <= config.target_ci
+                || samples.len() >= config.max_samples
+                || start.elapsed() >= config.time_budget)
+        {
+            return (samples, ci);
+        }
+    }
+}
+
+/// Generates a proof while sampling heap and RSS every `interval`, with phases of the harness
+/// marked.
+pub fn timeline<H: HashInSnark>(snark: &H, interval: Duration, rng: impl RngCore) -> Timeline {
+    let sampler = Sampler::start(interval);
+    let input = {
+        let _phase = timeline::enter("generate_input");
+        snark.generate_input(rng)
+    };
+    let proof = {
+        let _phase = timeline::enter("prove");
+        snark.prove(input)
+    };
+    {
+        let _phase = timeline::enter("serialize_proof");
+        black_box(H::serialize_proof(&proof));
+    }
+    {
+        let _phase = timeline::enter("drop_proof");
+        drop(black_box(proof));
+    }
+    sampler.stop()
+}
+
+/// Sets up and generates the first proof, measuring the one-time costs of both, and returns the
+/// serialized proof to be verified.
+pub fn cold_start<H: HashInSnark>(
     num_permutations: usize,
-    sample_size: usize,
-) -> (usize, Duration, f64, f64) {
-    let mut rng = StdRng::from_os_rng();
+    rng: impl RngCore,
+) -> (H, ColdStart, Vec<u8>) {
+    let baseline = alloc::reset_peak();
+
+    let start = Instant::now();
     let snark = H::new(num_permutations);
+    let setup_time = start.elapsed();
 
-    warm_up(&snark, &mut rng);
+    let (prove_time, bytes) = routine(&snark, rng);
 
-    let mut total_elapsed = Duration::default();
-    let mut total_proof_size = 0;
-    for _ in 0..sample_size {
-        let (elapsed, proof_size) = routine(&snark, &mut rng);
-        total_elapsed += elapsed;
-        total_proof_size += proof_size;
-    }
+    let cold = ColdStart {
+        num_permutations: snark.num_permutations(),
+        setup_time,
+        prove_time,
+        proof_size: bytes.len(),
+        peak_heap: alloc::is_tracking().then(|| alloc::peak().saturating_sub(baseline)),
+        peak_rss: peak_rss(),
+        description: None,
+    };
+    (snark, cold, bytes)
+}
 
-    let num_permutations = snark.num_permutations();
-    let time = total_elapsed / sample_size as u32;
-    let throughput = num_permutations as f64 / time.as_secs_f64();
-    let proof_size = total_proof_size as f64 / sample_size as f64;
-    (num_permutations, time, throughput, proof_size)
+pub fn bench<H: HashInSnark>(num_permutations: usize, config: &BenchConfig) -> Report {
+    Runner::<H>::new()
+        .sizes([num_permutations])
+        .config(config.clone())
+        .bench()
+        .remove(0)
 }
 
-pub fn noop() {}
+/// Runs [`cold_start`] and prints it as JSON, to be collected by [`bench_fresh_process`].
+pub fn print_cold_start<H: HashInSnark>(num_permutations: usize) {
+    let (snark, mut cold, bytes) = cold_start::<H>(num_permutations, InputSource::new());
+    if let Err(err) = check(&snark, &bytes) {
+        panic!("proof failed to verify: {err}");
+    }
+    cold.description = Some(snark.describe());
+    println!("{}", serde_json::to_string(&cold).unwrap());
+}
+
+/// Takes each sample by re-running the current binary with `--cold-start` in place of
+/// `--fresh-process`, so every sample pays the one-time costs.
+pub fn bench_fresh_process(config: &BenchConfig) -> FreshReport {
+    let args = env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--fresh-process")
+        .collect::<Vec<_>>();
+    let mut samples = Vec::new();
+    let (_, ci) = sample(config, || {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(&args)
+            .arg("--cold-start")
+            .stderr(Stdio::inherit())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "child failed: {}", output.status);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().rfind(|line| line.starts_with('{')).unwrap();
+        let cold: ColdStart = serde_json::from_str(line).unwrap();
+        let prove_time = cold.prove_time;
+        samples.push(cold);
+        prove_time
+    });
+    FreshReport { samples, ci }
+}
 
 #[macro_export]
 macro_rules! main {
-    (setup_trace = $setup_trace:path; hash = { $($variant:ident => $snark:ty),+ $(,)? };) => {
-        #[derive(Clone, Debug, clap::ValueEnum)]
-        enum Hash {
-            $($variant),+
-        }
-
+    ($($variant:ident $(@ $config:ident)? => $snark:ty),+ $(,)?) => {
         #[derive(Clone, Debug, clap::Parser)]
         #[command(version, about)]
         struct Args {
-            #[arg(long, value_enum)]
-            hash: Hash,
+            /// Hash to prove, optionally with its configuration as `<HASH>@<CONFIG>`.
+            #[arg(long, required_unless_present_any = ["list", "interleave"])]
+            hash: Option<String>,
+            /// Named configuration of the hash, defaulting to its first one listed by `--list`.
             #[arg(long)]
-            log_permutations: usize,
+            config: Option<String>,
+            /// Lists every `<hash>@<config>` supported.
+            #[arg(long, default_value_t = false, exclusive = true)]
+            list: bool,
+            #[arg(long, required_unless_present = "list")]
+            log_permutations: Option<usize>,
+            /// Minimum number of samples, runs a single proof without measuring if not given.
             #[arg(long)]
             sample_size: Option<usize>,
-            #[arg(long, default_value_t = false)]
-            trace: bool
+            /// Maximum number of samples.
+            #[arg(long, default_value_t = 100)]
+            max_sample_size: usize,
+            /// Minimum number of warm-up iterations.
+            #[arg(long, default_value_t = 1)]
+            warm_up_iters: usize,
+            /// Minimum warm-up time in seconds.
+            #[arg(long, default_value_t = 3.0)]
+            warm_up_time: f64,
+            /// Target width of the 95% confidence interval of the median time, relative to the
+            /// median.
+            #[arg(long, default_value_t = 0.02)]
+            target_ci: f64,
+            /// Time budget of sampling in seconds.
+            #[arg(long, default_value_t = 60.0)]
+            time_budget: f64,
+            /// Samples heap and RSS every given milliseconds during an extra proof, and reports
+            /// peaks of each phase.
+            #[arg(long)]
+            timeline: Option<f64>,
+            /// Takes each sample in a fresh process to measure cold starts.
+            #[arg(long, default_value_t = false, conflicts_with = "output")]
+            fresh_process: bool,
+            /// Writes the report as JSON to the given path, to be rendered by `bench`'s `report`.
+            #[arg(long)]
+            output: Option<std::path::PathBuf>,
+            #[arg(long, default_value_t = false, hide = true)]
+            cold_start: bool,
+            /// Proves the permutations in chunks by concurrent workers whose total heap fits in
+            /// the given MiB, with the chunk size of maximum throughput unless given.
+            #[arg(long, conflicts_with_all = ["fresh_process", "output"])]
+            memory_budget: Option<usize>,
+            /// Log of permutations per chunk of `--memory-budget`.
+            #[arg(long, requires = "memory_budget")]
+            chunk_log_permutations: Option<usize>,
+            /// Maximum number of concurrent workers of `--memory-budget`, defaults to the number
+            /// of threads.
+            #[arg(long, requires = "memory_budget")]
+            max_concurrency: Option<usize>,
+            /// Proves the given number of proofs at the same time, each on its own pool of
+            /// `--threads-per-proof` threads, and reports aggregate throughput and latency.
+            #[arg(
+                long,
+                conflicts_with_all = ["fresh_process", "output", "memory_budget"],
+                value_parser = clap::value_parser!(u64).range(1..),
+            )]
+            concurrency: Option<u64>,
+            /// Number of threads of each proof of `--concurrency`.
+            #[arg(long, default_value_t = 1, requires = "concurrency")]
+            threads_per_proof: usize,
+            /// Number of proofs of each worker of `--concurrency`.
+            #[arg(
+                long,
+                default_value_t = 10,
+                requires = "concurrency",
+                value_parser = clap::value_parser!(u64).range(1..),
+            )]
+            proofs_per_worker: u64,
+            /// Proves repeatedly for the given seconds, and flags growth of latency, heap or RSS
+            /// between the first and last third of proofs beyond `--soak-threshold`.
+            #[arg(
+                long,
+                conflicts_with_all = ["fresh_process", "output", "memory_budget", "concurrency"],
+            )]
+            soak: Option<f64>,
+            /// Relative growth flagged by `--soak`.
+            #[arg(long, default_value_t = 0.1, requires = "soak")]
+            soak_threshold: f64,
+            /// Encodes a proof by each codec, and reports sizes and median encoding and decoding
+            /// times of `--sample-size` (default 10) samples.
+            #[arg(
+                long,
+                default_value_t = false,
+                conflicts_with_all = ["fresh_process", "output", "memory_budget", "concurrency", "soak"],
+            )]
+            codecs: bool,
+            /// Alternates samples of the given `<HASH>[@<CONFIG>]`s set up in this process, and
+            /// reports the difference of each to the first with its significance.
+            #[arg(
+                long,
+                value_delimiter = ',',
+                conflicts_with_all = ["hash", "config", "fresh_process", "output", "memory_budget", "concurrency", "soak", "codecs"],
+            )]
+            interleave: Vec<String>,
+            #[arg(long, default_value_t = false, hide = true)]
+            serve: bool,
+            /// Pins threads to the given CPUs, e.g. `0-7,16-23`, only available on Linux.
+            #[arg(long)]
+            cpus: Option<String>,
+            /// Action on noise found before the run: CPU scaling governors other than
+            /// `performance`, load, SMT siblings or multiple NUMA nodes among the CPUs.
+            #[arg(long, value_enum, default_value_t = $crate::preflight::Policy::Warn)]
+            preflight: $crate::preflight::Policy,
+            /// Load average of the last minute above which the machine is noisy.
+            #[arg(long, default_value_t = 1.0)]
+            max_load: f64,
+            /// Derives permutation inputs from the bytes of the given file, or stdin if `-`, instead
+            /// of random bytes.
+            #[arg(long)]
+            input: Option<std::path::PathBuf>,
+            /// Collects tracing spans into Chrome trace events `<TRACE>.json` and folded stacks
+            /// `<TRACE>.folded`.
+            #[arg(long)]
+            trace: Option<std::path::PathBuf>,
+        }
+
+        #[global_allocator]
+        static ALLOC: $crate::alloc::TrackingAllocator = $crate::alloc::TrackingAllocator;
+
+        /// Pairs of hash and config names, with the default config of each hash first.
+        fn configs() -> Vec<(String, &'static str)> {
+            vec![$((
+                $crate::util::kebab_case(stringify!($variant)),
+                [$(stringify!($config),)? "default"][0],
+            )),+]
         }
 
         fn main() {
             let args: Args = clap::Parser::parse();
 
-            if args.trace {
-                $setup_trace();
+            let configs = configs();
+            if args.list {
+                for (hash, config) in &configs {
+                    println!("{hash}@{config}");
+                }
+                return;
             }
 
-            let Some(sample_size) = args.sample_size else {
-                match args.hash {
-                    $(Hash::$variant => $crate::run::<$snark>(1 << args.log_permutations)),+
+            if let Some(cpus) = &args.cpus {
+                let list = $crate::preflight::parse_cpu_list(cpus).unwrap_or_else(|| {
+                    error(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!("invalid CPU list `{cpus}`"),
+                    )
+                });
+                $crate::preflight::pin(&list).unwrap_or_else(|err| {
+                    error(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!("failed to pin threads to CPUs `{cpus}`: {err}"),
+                    )
+                });
+            }
+            // Children of `--fresh-process` and `interleave` are checked by their parent.
+            if args.preflight != $crate::preflight::Policy::Off && !args.cold_start && !args.serve {
+                let warnings = $crate::preflight::check(args.max_load);
+                for warning in warnings {
+                    eprintln!("warning: {warning}");
                 }
-                return;
+                if args.preflight == $crate::preflight::Policy::Abort && !warnings.is_empty() {
+                    eprintln!("error: machine is noisy, see `--preflight`");
+                    std::process::exit(1);
+                }
+            }
+
+            let _trace = args.trace.clone().map($crate::trace::init);
+
+            let select = |hash: &str, config: Option<&str>| {
+                $crate::util::select_config(&configs, hash, config)
+                    .unwrap_or_else(|err| error(clap::error::ErrorKind::InvalidValue, err))
             };
 
-            let num_permutations = 1 << args.log_permutations;
-            let (_, time, throughput, proof_size) = match args.hash {
-                $(Hash::$variant => $crate::bench::<$snark>(num_permutations, sample_size)),+
+            if args.interleave.len() == 1 {
+                error(
+                    clap::error::ErrorKind::TooFewValues,
+                    "`--interleave` needs at least 2 configurations".to_string(),
+                )
+            }
+            let selected = if args.interleave.is_empty() {
+                vec![select(args.hash.as_deref().unwrap(), args.config.as_deref())]
+            } else {
+                args.interleave.iter().map(|hash| select(hash, None)).collect()
             };
-            println!(
-                "      time: {}\nthroughput: {}\nproof size: {}",
-                $crate::util::human_time(time),
-                $crate::util::human_throughput(throughput),
-                $crate::util::human_size(proof_size),
-            );
+
+            if let Some(input) = &args.input {
+                let takes_input = [$(<$snark as $crate::HashInSnark>::TAKES_INPUT),+];
+                if let Some((_, hash)) = selected.iter().find(|(index, _)| !takes_input[*index]) {
+                    error(
+                        clap::error::ErrorKind::ArgumentConflict,
+                        format!("`--input` is unsupported by `{hash}`, which generates its own inputs"),
+                    )
+                }
+                $crate::input::init(input).unwrap();
+            }
+
+            if !args.interleave.is_empty() {
+                let instances: &[fn(String, usize) -> Box<dyn $crate::interleave::Contender>] =
+                    &[$($crate::interleave::instance::<$snark>),+];
+                let num_permutations = 1 << args.log_permutations.unwrap();
+                let mut contenders = selected
+                    .into_iter()
+                    .map(|(index, label)| instances[index](label, num_permutations))
+                    .collect::<Vec<_>>();
+                let config = bench_config(&args, args.sample_size.unwrap_or(10));
+                let report = $crate::interleave::interleave(&mut contenders, &config).unwrap();
+                println!("{report}");
+                if !report.verify_failures.is_empty() {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            let (index, hash) = selected.into_iter().next().unwrap();
+            let runs: &[fn(Args, &str)] = &[$(run::<$snark>),+];
+            runs[index](args, &hash);
+        }
+
+        fn error(kind: clap::error::ErrorKind, message: String) -> ! {
+            <Args as clap::CommandFactory>::command()
+                .error(kind, message)
+                .exit()
+        }
+
+        fn bench_config(args: &Args, min_samples: usize) -> $crate::BenchConfig {
+            $crate::BenchConfig {
+                warm_up_iters: args.warm_up_iters,
+                warm_up_time: std::time::Duration::from_secs_f64(args.warm_up_time),
+                min_samples,
+                max_samples: args.max_sample_size.max(min_samples),
+                target_ci: args.target_ci,
+                time_budget: std::time::Duration::from_secs_f64(args.time_budget),
+                timeline_interval: args
+                    .timeline
+                    .map(|ms| std::time::Duration::from_secs_f64(ms / 1000.0)),
+            }
+        }
+
+        /// Runs the mode selected by `args` on `H`, labeled `hash` in records.
+        fn run<H: $crate::HashInSnark>(args: Args, hash: &str) {
+            let log_permutations = args.log_permutations.unwrap();
+            let num_permutations = 1 << log_permutations;
+
+            if args.serve {
+                $crate::interleave::serve::<H>(num_permutations);
+                return;
+            }
+
+            if args.cold_start {
+                $crate::print_cold_start::<H>(num_permutations);
+                return;
+            }
+
+            if let Some(memory_budget) = args.memory_budget {
+                let config = $crate::chunked::ChunkedConfig {
+                    memory_budget: memory_budget << 20,
+                    chunk_log_permutations: args.chunk_log_permutations,
+                    max_concurrency: args.max_concurrency,
+                    ..Default::default()
+                };
+                let report = $crate::chunked::chunked::<H>(num_permutations, &config);
+                println!("{report}");
+                if !report.verify_failures.is_empty() {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            if let Some(concurrency) = args.concurrency {
+                let config = $crate::concurrent::ConcurrentConfig {
+                    concurrency: concurrency as usize,
+                    threads_per_proof: args.threads_per_proof,
+                    proofs_per_worker: args.proofs_per_worker as usize,
+                };
+                let report = $crate::concurrent::concurrent::<H>(num_permutations, &config);
+                println!("{report}");
+                return;
+            }
+
+            if let Some(soak) = args.soak {
+                let config = $crate::soak::SoakConfig {
+                    duration: std::time::Duration::from_secs_f64(soak),
+                    threshold: args.soak_threshold,
+                };
+                let report = $crate::soak::soak::<H>(num_permutations, &config);
+                println!("{report}");
+                if !report.flags().is_empty() {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            if args.codecs {
+                let num_samples = args.sample_size.unwrap_or(10);
+                let report = $crate::codec::codecs::<H>(num_permutations, num_samples);
+                println!("{report}");
+                return;
+            }
+
+            let Some(sample_size) = args.sample_size else {
+                $crate::runner::Runner::<H>::new()
+                    .sizes([num_permutations])
+                    .run();
+                return;
+            };
+
+            let config = bench_config(&args, sample_size);
+            if args.fresh_process {
+                println!("{}", $crate::bench_fresh_process(&config));
+                return;
+            }
+            let report = $crate::runner::Runner::<H>::new()
+                .sizes([num_permutations])
+                .config(config)
+                .bench()
+                .remove(0);
+            println!("{report}");
+            let valid = report.verify_failures.is_empty();
+            if let Some(output) = args.output {
+                let record = $crate::report::Record::new(
+                    env!("CARGO_PKG_NAME"),
+                    hash,
+                    log_permutations,
+                    report,
+                );
+                record.write(output).unwrap();
+            }
+            if !valid {
+                std::process::exit(1);
+            }
         }
     };
-    ($($variant:ident => $snark:ty),+ $(,)?) => {
-        main!(
-            setup_trace = $crate::noop;
-            hash = { $($variant => $snark),+ };
-        );
-    }
 }
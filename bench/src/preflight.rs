@@ -0,0 +1,197 @@
+//! Checks of the machine for noise skewing results before a run, and pinning of threads to CPUs,
+//! only available on Linux.
+//!
+//! Warnings of [`check`] and CPUs of [`pin`] are kept for the process and included in reports.
+
+use std::{fs, io, path::Path, sync::OnceLock};
+
+/// Action on warnings of [`check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Policy {
+    /// Prints warnings and runs.
+    Warn,
+    /// Prints warnings and exits with failure if there are any.
+    Abort,
+    /// Skips checking.
+    Off,
+}
+
+static WARNINGS: OnceLock<Vec<String>> = OnceLock::new();
+static PINNED: OnceLock<Vec<usize>> = OnceLock::new();
+
+/// Returns the warnings of [`check`], or none if it hasn't run.
+pub fn warnings() -> &'static [String] {
+    WARNINGS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Returns the CPUs pinned by [`pin`].
+pub fn pinned() -> Option<&'static [usize]> {
+    PINNED.get().map(Vec::as_slice)
+}
+
+/// Checks the CPUs pinned by [`pin`] (or all online CPUs) for scaling governors other than
+/// `performance`, SMT siblings and multiple NUMA nodes, and the load average of the last minute
+/// for being above `max_load`. Returns the warnings, which are kept for reports.
+pub fn check(max_load: f64) -> &'static [String] {
+    let cpu = Path::new("/sys/devices/system/cpu");
+    let cpus = match pinned() {
+        Some(cpus) => cpus.to_vec(),
+        None => read_cpu_list(cpu.join("online")).unwrap_or_default(),
+    };
+    let mut warnings = Vec::new();
+
+    let mut governors = Vec::<(String, Vec<usize>)>::new();
+    for &id in &cpus {
+        let path = cpu.join(format!("cpu{id}/cpufreq/scaling_governor"));
+        let Ok(governor) = fs::read_to_string(path) else {
+            continue;
+        };
+        let governor = governor.trim();
+        match governors.iter_mut().find(|(other, _)| other == governor) {
+            Some((_, ids)) => ids.push(id),
+            None => governors.push((governor.to_string(), vec![id])),
+        }
+    }
+    for (governor, ids) in governors {
+        if governor != "performance" {
+            warnings.push(format!(
+                "CPUs {} have scaling governor `{governor}` rather than `performance`",
+                format_cpu_list(&ids)
+            ));
+        }
+    }
+
+    if let Some(load) = fs::read_to_string("/proc/loadavg")
+        .ok()
+        .and_then(|loadavg| loadavg.split_whitespace().next()?.parse::<f64>().ok())
+    {
+        if load > max_load {
+            warnings.push(format!(
+                "load average of the last minute is {load:.2}, above {max_load:.2}"
+            ));
+        }
+    }
+
+    let siblings = cpus
+        .iter()
+        .filter(|id| {
+            let path = cpu.join(format!("cpu{id}/topology/thread_siblings_list"));
+            read_cpu_list(path).is_ok_and(|siblings| {
+                siblings
+                    .iter()
+                    .any(|sibling| sibling != *id && cpus.contains(sibling))
+            })
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    if !siblings.is_empty() {
+        warnings.push(format!(
+            "CPUs {} share cores with SMT siblings",
+            format_cpu_list(&siblings)
+        ));
+    }
+
+    let nodes = fs::read_dir("/sys/devices/system/node")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("node"))
+        .filter_map(|entry| read_cpu_list(entry.path().join("cpulist")).ok())
+        .filter(|node| node.iter().any(|id| cpus.contains(id)))
+        .count();
+    if nodes > 1 {
+        warnings.push(format!(
+            "CPUs span {nodes} NUMA nodes, so memory may be remote to threads"
+        ));
+    }
+
+    WARNINGS.get_or_init(|| warnings)
+}
+
+/// Pins the current thread to `cpus`, which is inherited by threads spawned after, including the
+/// rayon worker threads if the global pool isn't started yet.
+#[cfg(target_os = "linux")]
+pub fn pin(cpus: &[usize]) -> io::Result<()> {
+    let max = 8 * size_of::<libc::cpu_set_t>();
+    if let Some(cpu) = cpus.iter().find(|cpu| **cpu >= max) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CPU {cpu} is out of range"),
+        ));
+    }
+    // SAFETY: `set` is a valid `cpu_set_t` of the given size holding `cpus`, and pid 0 is the
+    // calling thread.
+    unsafe {
+        let mut set = core::mem::zeroed::<libc::cpu_set_t>();
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    PINNED
+        .set(cpus.to_vec())
+        .map_err(|_| io::Error::other("threads are already pinned"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pinning threads is only available on Linux",
+    ))
+}
+
+/// Parses a list of CPUs in the format of sysfs and `taskset`, e.g. `0-3,8,10-11`.
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+fn read_cpu_list(path: impl AsRef<Path>) -> io::Result<Vec<usize>> {
+    let list = fs::read_to_string(path)?;
+    parse_cpu_list(&list).ok_or_else(|| io::Error::other(format!("invalid CPU list {list}")))
+}
+
+/// Formats `cpus` in the format of [`parse_cpu_list`].
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges = Vec::<(usize, usize)>::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::preflight::{format_cpu_list, parse_cpu_list};
+
+    #[test]
+    fn cpu_list() {
+        let cpus = parse_cpu_list("0-3,8,10-11\n").unwrap();
+        assert_eq!(cpus, [0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(format_cpu_list(&cpus), "0-3,8,10-11");
+        assert_eq!(parse_cpu_list("1-x"), None);
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+    }
+}
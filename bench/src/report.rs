@@ -0,0 +1,459 @@
+use crate::{
+    cost::VerifierCost,
+    preflight::format_cpu_list,
+    stats,
+    timeline::Timeline,
+    util::{human_size, human_throughput, human_time, pcs_log_inv_rate, pcs_params, PcsParams},
+    workload::Workload,
+};
+use core::fmt::{self, Display};
+use rayon::current_num_threads;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub mod fit;
+pub mod markdown;
+pub mod svg;
+
+/// Proof system of a backend, returned by [`HashInSnark::describe`](crate::HashInSnark::describe).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Description {
+    /// Base field of the trace, e.g. `BabyBear` or `GF(2)`.
+    pub field: String,
+    /// Degree of the field of challenges over the base field.
+    pub extension_degree: usize,
+    /// Constraint system proven, e.g. `AIR` or `GKR`.
+    pub arithmetization: String,
+    pub pcs: String,
+    /// Hash of the Merkle trees of the PCS, if it has any.
+    pub merkle_hash: Option<String>,
+    /// Hash of the Fiat-Shamir transcript.
+    pub transcript_hash: String,
+    pub log_inv_rate: Option<usize>,
+    /// Number of queries, if not derived by the backend from `security_bits`.
+    pub num_queries: Option<usize>,
+    /// Target of security bits the backend derives its queries from.
+    pub security_bits: Option<usize>,
+    /// Bits of grinding before queries.
+    pub pow_bits: Option<usize>,
+}
+
+impl Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.field)?;
+        if self.extension_degree > 1 {
+            write!(f, " (ext {})", self.extension_degree)?;
+        }
+        write!(f, ", {}, {}", self.arithmetization, self.pcs)?;
+        if let Some(log_inv_rate) = self.log_inv_rate {
+            write!(f, " rate 1/{}", 1 << log_inv_rate)?;
+        }
+        if let Some(num_queries) = self.num_queries {
+            write!(f, ", {num_queries} queries")?;
+        }
+        if let Some(security_bits) = self.security_bits {
+            write!(f, ", {security_bits} security bits")?;
+        }
+        if let Some(pow_bits) = self.pow_bits {
+            write!(f, ", {pow_bits} pow bits")?;
+        }
+        if let Some(merkle_hash) = &self.merkle_hash {
+            write!(f, ", {merkle_hash} Merkle")?;
+        }
+        write!(f, ", {} transcript", self.transcript_hash)
+    }
+}
+
+/// One-time costs paid by the first proof in a process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ColdStart {
+    pub num_permutations: usize,
+    /// Time of `HashInSnark::new`.
+    pub setup_time: Duration,
+    /// Time of the first proof.
+    pub prove_time: Duration,
+    pub proof_size: usize,
+    /// Peak heap allocated during setup and the first proof.
+    pub peak_heap: Option<usize>,
+    /// Peak resident set size of the process after the first proof.
+    pub peak_rss: Option<usize>,
+    /// Proof system, only given by processes of [`FreshReport`] as [`Report`] has its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Description>,
+}
+
+impl Display for ColdStart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "cold setup: {}", human_time(self.setup_time))?;
+        write!(f, "cold prove: {}", human_time(self.prove_time))?;
+        if let Some(peak_heap) = self.peak_heap {
+            write!(f, "\n cold heap: {}", human_size(peak_heap as f64))?;
+        }
+        if let Some(peak_rss) = self.peak_rss {
+            write!(f, "\n  cold rss: {}", human_size(peak_rss as f64))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Report {
+    /// Name of the permutation, i.e. `HashDescriptor::NAME`.
+    pub permutation: String,
+    /// Computation proven, i.e. `HashInSnark::workload`.
+    #[serde(default)]
+    pub workload: Workload,
+    pub num_permutations: usize,
+    /// Median proving time, i.e. per tree of [`Workload::MerkleTree`].
+    pub time: Duration,
+    /// Permutations per second.
+    pub throughput: f64,
+    /// Message bytes absorbed per second.
+    pub absorbed_throughput: f64,
+    /// Proving time per 2-to-1 compression with 128-bit collision security, or per compression of
+    /// the tree of [`Workload::MerkleTree`].
+    pub compression_time: Duration,
+    pub proof_size: f64,
+    /// Median verification time of a deserialized proof, unless skipped by
+    /// [`Metrics`](crate::runner::Metrics).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_time: Option<Duration>,
+    /// Hashing done by a verification, if the backend counts it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier_cost: Option<VerifierCost>,
+    pub num_samples: usize,
+    /// Errors of the proofs failing to verify, of the samples, the cold start and the proof of
+    /// verifying time, each verified after a roundtrip through serialization.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify_failures: Vec<String>,
+    /// Width of the 95% confidence interval of the median time, relative to the median.
+    pub ci: f64,
+    pub cold: ColdStart,
+    /// Peak heap allocated while sampling, on top of the heap allocated before.
+    pub peak_heap: Option<usize>,
+    /// Peak resident set size of the process.
+    pub peak_rss: Option<usize>,
+    /// Heap and RSS sampled during an extra proof.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeline: Option<Timeline>,
+    /// Proof system, absent in records of older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Description>,
+    /// CPUs the threads were pinned to by [`preflight::pin`](crate::preflight::pin).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<Vec<usize>>,
+    /// Warnings of noise found before the run by [`preflight::check`](crate::preflight::check).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub noise: Vec<String>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(description) = &self.description {
+            writeln!(f, "    system: {description}")?;
+        }
+        if let Some(cpus) = &self.cpus {
+            writeln!(f, "    pinned: CPUs {}", format_cpu_list(cpus))?;
+        }
+        match self.workload {
+            Workload::Permutations => {}
+            Workload::MerkleTree => writeln!(
+                f,
+                "  workload: Merkle tree of {} leaves, {} compressions",
+                self.num_permutations + 1,
+                self.num_permutations
+            )?,
+            Workload::Chains { length } => writeln!(
+                f,
+                "  workload: {} chains of {length} permutations",
+                self.num_permutations / length
+            )?,
+        }
+        writeln!(f, "      time: {}", human_time(self.time))?;
+        writeln!(f, "throughput: {}", human_throughput(self.throughput))?;
+        writeln!(f, "  absorbed: {}/s", human_size(self.absorbed_throughput))?;
+        writeln!(f, "  compress: {}", human_time(self.compression_time))?;
+        writeln!(f, "proof size: {}", human_size(self.proof_size))?;
+        if let Some(verify_time) = self.verify_time {
+            writeln!(f, "    verify: {}", human_time(verify_time))?;
+        }
+        if let Some(cost) = &self.verifier_cost {
+            writeln!(
+                f,
+                " verify io: {} permutations, {} hashes of {}",
+                cost.permutations,
+                cost.hashes,
+                human_size(cost.bytes as f64)
+            )?;
+        }
+        if let Some(peak_rss) = self.peak_rss {
+            writeln!(f, "  peak mem: {}", human_size(peak_rss as f64))?;
+        }
+        if let Some(peak_heap) = self.peak_heap {
+            writeln!(f, " peak heap: {}", human_size(peak_heap as f64))?;
+        }
+        writeln!(f, "{}", self.cold)?;
+        if let Some(timeline) = &self.timeline {
+            writeln!(
+                f,
+                "  timeline: {} samples every {}",
+                timeline.samples.len(),
+                human_time(timeline.interval)
+            )?;
+            let size = |size: Option<usize>| size.map_or("-".to_string(), |s| human_size(s as f64));
+            for peak in timeline.phase_peaks() {
+                let phase = if peak.phase.is_empty() {
+                    "-"
+                } else {
+                    &peak.phase
+                };
+                writeln!(
+                    f,
+                    "            {phase}: heap {}, rss {}",
+                    size(peak.heap),
+                    size(peak.rss)
+                )?;
+            }
+        }
+        write!(
+            f,
+            "confidence: ±{:.2}% ({} samples)",
+            self.ci * 50.0,
+            self.num_samples
+        )?;
+        if let Some(err) = self.verify_failures.first() {
+            write!(
+                f,
+                "\n   invalid: {} of {} proofs failed to verify, first with {err}",
+                self.verify_failures.len(),
+                self.num_samples + 2,
+            )?;
+        }
+        for warning in &self.noise {
+            write!(f, "\n     noise: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Cold starts of proofs each in a fresh process.
+#[derive(Clone, Debug)]
+pub struct FreshReport {
+    pub samples: Vec<ColdStart>,
+    /// Width of the 95% confidence interval of the median prove time, relative to the median.
+    pub ci: f64,
+}
+
+impl FreshReport {
+    /// Returns the sample with median of each field.
+    pub fn median(&self) -> ColdStart {
+        let median = |f: fn(&ColdStart) -> f64| {
+            stats::median(&self.samples.iter().map(f).collect::<Vec<_>>())
+        };
+        let median_opt = |f: fn(&ColdStart) -> Option<usize>| {
+            let values = self.samples.iter().filter_map(f).collect::<Vec<_>>();
+            (!values.is_empty())
+                .then(|| stats::median(&values.iter().map(|v| *v as f64).collect::<Vec<_>>()))
+                .map(|v| v as usize)
+        };
+        ColdStart {
+            num_permutations: self.samples[0].num_permutations,
+            setup_time: Duration::from_secs_f64(median(|s| s.setup_time.as_secs_f64())),
+            prove_time: Duration::from_secs_f64(median(|s| s.prove_time.as_secs_f64())),
+            proof_size: median(|s| s.proof_size as f64) as usize,
+            peak_heap: median_opt(|s| s.peak_heap),
+            peak_rss: median_opt(|s| s.peak_rss),
+            description: self.samples[0].description.clone(),
+        }
+    }
+}
+
+impl Display for FreshReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let median = self.median();
+        let throughput = median.num_permutations as f64 / median.prove_time.as_secs_f64();
+        if let Some(description) = &median.description {
+            writeln!(f, "    system: {description}")?;
+        }
+        writeln!(f, "{median}")?;
+        writeln!(f, "throughput: {}", human_throughput(throughput))?;
+        writeln!(f, "proof size: {}", human_size(median.proof_size as f64))?;
+        write!(
+            f,
+            "confidence: ±{:.2}% ({} processes)",
+            self.ci * 50.0,
+            self.samples.len()
+        )
+    }
+}
+
+/// [`Report`] with the configuration it was measured with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Record {
+    /// Name of the backend crate, e.g. `bench-plonky3`.
+    pub package: String,
+    /// Name of the hash in the backend binary, e.g. `keccak`.
+    pub hash: String,
+    pub num_threads: usize,
+    pub log_inv_rate: usize,
+    /// PCS parameters given by env, absent in records of older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pcs: Option<PcsParams>,
+    pub log_permutations: usize,
+    pub report: Report,
+}
+
+impl Record {
+    /// Returns record of `report` measured in the current process.
+    pub fn new(package: &str, hash: &str, log_permutations: usize, report: Report) -> Self {
+        Self {
+            package: package.to_string(),
+            hash: hash.to_string(),
+            num_threads: current_num_threads(),
+            log_inv_rate: pcs_log_inv_rate(),
+            pcs: Some(pcs_params()),
+            log_permutations,
+            report,
+        }
+    }
+
+    /// Returns the backend name without the `bench-` prefix.
+    pub fn backend(&self) -> &str {
+        self.package.strip_prefix("bench-").unwrap_or(&self.package)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Returns the records not dominated by another in proving time, verifying time and proof size,
+/// sorted by proving time. Records without verifying time are skipped.
+pub fn pareto_front<'a>(records: &[&'a Record]) -> Vec<&'a Record> {
+    let records = records
+        .iter()
+        .filter(|record| record.report.verify_time.is_some())
+        .copied()
+        .collect::<Vec<_>>();
+    let costs = |record: &Record| {
+        let report = &record.report;
+        [
+            report.time.as_secs_f64(),
+            report.verify_time.unwrap().as_secs_f64(),
+            report.proof_size,
+        ]
+    };
+    let dominates = |a: &Record, b: &Record| {
+        let (a, b) = (costs(a), costs(b));
+        a.iter().zip(&b).all(|(a, b)| a <= b) && a != b
+    };
+    let mut front = records
+        .iter()
+        .filter(|record| !records.iter().any(|other| dominates(other, record)))
+        .copied()
+        .collect::<Vec<_>>();
+    front.sort_by_key(|record| record.report.time);
+    front
+}
+
+/// Loads records from JSON files, each containing a [`Record`] or a list of them.
+pub fn load(paths: impl IntoIterator<Item = impl AsRef<Path>>) -> io::Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for path in paths {
+        let bytes = fs::read(path.as_ref())?;
+        match serde_json::from_slice::<Vec<Record>>(&bytes) {
+            Ok(batch) => records.extend(batch),
+            Err(_) => records.push(serde_json::from_slice(&bytes)?),
+        }
+    }
+    Ok(records)
+}
+
+/// Returns paths of `<package>/report/*.json` under `root`.
+pub fn find(root: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for package in fs::read_dir(root)? {
+        let Ok(entries) = fs::read_dir(package?.path().join("report")) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::report::{pareto_front, ColdStart, Record, Report};
+    use std::time::Duration;
+
+    fn record(time: u64, verify_time: Option<u64>, proof_size: f64) -> Record {
+        let report = Report {
+            permutation: String::new(),
+            workload: Default::default(),
+            num_permutations: 1,
+            time: Duration::from_millis(time),
+            throughput: 0.0,
+            absorbed_throughput: 0.0,
+            compression_time: Duration::ZERO,
+            proof_size,
+            verify_time: verify_time.map(Duration::from_millis),
+            verifier_cost: None,
+            num_samples: 1,
+            verify_failures: Vec::new(),
+            ci: 0.0,
+            cold: ColdStart {
+                num_permutations: 1,
+                setup_time: Duration::ZERO,
+                prove_time: Duration::ZERO,
+                proof_size: 0,
+                peak_heap: None,
+                peak_rss: None,
+                description: None,
+            },
+            peak_heap: None,
+            peak_rss: None,
+            timeline: None,
+            description: None,
+            cpus: None,
+            noise: Vec::new(),
+        };
+        Record {
+            package: String::new(),
+            hash: String::new(),
+            num_threads: 1,
+            log_inv_rate: 1,
+            pcs: None,
+            log_permutations: 0,
+            report,
+        }
+    }
+
+    #[test]
+    fn pareto() {
+        let records = [
+            record(3, Some(1), 100.0),
+            record(1, Some(2), 200.0),
+            // Dominated by the first, and by an equal record but not by itself.
+            record(3, Some(2), 100.0),
+            record(1, Some(2), 200.0),
+            record(2, Some(1), 300.0),
+            record(1, None, 1.0),
+        ];
+        let front = pareto_front(&records.iter().collect::<Vec<_>>());
+        let costs = front
+            .iter()
+            .map(|r| (r.report.time.as_millis(), r.report.proof_size))
+            .collect::<Vec<_>>();
+        assert_eq!(costs, [(1, 200.0), (1, 200.0), (2, 300.0), (3, 100.0)]);
+    }
+}
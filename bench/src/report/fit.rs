@@ -0,0 +1,154 @@
+//! Fits of measurements against the number of permutations `n`, to extrapolate to sizes too large
+//! to run and to find sizes deviating from the trend, e.g. at cache cliffs.
+//!
+//! Each model is `y = a + b * f(n)`, fitted by least squares of residuals relative to `y`, since
+//! measurements span orders of magnitude and their noise is roughly proportional to them.
+
+use crate::{report::markdown::Column, stats};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    Linear,
+    NLogN,
+    Log,
+    LogSquared,
+}
+
+impl Model {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "n",
+            Self::NLogN => "n log n",
+            Self::Log => "log n",
+            Self::LogSquared => "log² n",
+        }
+    }
+
+    fn feature(self, log_n: f64) -> f64 {
+        match self {
+            Self::Linear => log_n.exp2(),
+            Self::NLogN => log_n.exp2() * log_n,
+            Self::Log => log_n,
+            Self::LogSquared => log_n * log_n,
+        }
+    }
+}
+
+/// Columns fitted and their candidate models.
+pub const QUANTITIES: [(Column, &[Model]); 3] = [
+    (Column::Time, &[Model::Linear, Model::NLogN]),
+    (Column::PeakMem, &[Model::Linear, Model::NLogN]),
+    (Column::ProofSize, &[Model::Log, Model::LogSquared]),
+];
+
+#[derive(Clone, Debug)]
+pub struct Fit {
+    pub model: Model,
+    pub intercept: f64,
+    pub slope: f64,
+    /// Standard deviation of residuals relative to the measurements.
+    pub sigma: f64,
+    /// Coefficient of determination of the weighted fit.
+    pub r_squared: f64,
+    /// Total weight, weighted mean and weighted sum of squared deviations of the features.
+    weight: f64,
+    mean: f64,
+    sxx: f64,
+}
+
+impl Fit {
+    /// Fits `model` to `(log_n, y)` points, returning `None` with fewer than 3 distinct sizes.
+    pub fn new(model: Model, points: &[(usize, f64)]) -> Option<Self> {
+        let points = points.iter().filter(|(_, y)| *y > 0.0).collect::<Vec<_>>();
+        let sizes = points.iter().map(|(log_n, _)| *log_n);
+        if sizes.collect::<BTreeSet<_>>().len() < 3 {
+            return None;
+        }
+
+        let xs = points
+            .iter()
+            .map(|(log_n, _)| model.feature(*log_n as f64))
+            .collect::<Vec<_>>();
+        let ys = points.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+        let ws = ys.iter().map(|y| y.powi(-2)).collect::<Vec<_>>();
+        let (slope, intercept) = stats::weighted_linear_fit(&xs, &ys, &ws);
+
+        let weight = ws.iter().sum::<f64>();
+        let mean = xs.iter().zip(&ws).map(|(x, w)| x * w).sum::<f64>() / weight;
+        let sxx = xs
+            .iter()
+            .zip(&ws)
+            .map(|(x, w)| w * (x - mean).powi(2))
+            .sum();
+        let mean_y = ys.iter().zip(&ws).map(|(y, w)| y * w).sum::<f64>() / weight;
+        let (rss, tss) =
+            (xs.iter().zip(&ys).zip(&ws)).fold((0.0, 0.0), |(rss, tss), ((x, y), w)| {
+                (
+                    rss + w * (y - intercept - slope * x).powi(2),
+                    tss + w * (y - mean_y).powi(2),
+                )
+            });
+        Some(Self {
+            model,
+            intercept,
+            slope,
+            sigma: (rss / (points.len() - 2) as f64).sqrt(),
+            r_squared: 1.0 - rss / tss,
+            weight,
+            mean,
+            sxx,
+        })
+    }
+
+    /// Returns the best fit of `models` by the smallest relative residuals.
+    pub fn best(models: &[Model], points: &[(usize, f64)]) -> Option<Self> {
+        models
+            .iter()
+            .filter_map(|model| Self::new(*model, points))
+            .min_by(|a, b| a.sigma.total_cmp(&b.sigma))
+    }
+
+    pub fn predict(&self, log_n: usize) -> f64 {
+        self.intercept + self.slope * self.model.feature(log_n as f64)
+    }
+
+    /// Returns the prediction at `2^log_n` and the half width of its approximate 95% prediction
+    /// interval, which accounts for the uncertainty of the fit and the noise of a measurement.
+    pub fn extrapolate(&self, log_n: usize) -> (f64, f64) {
+        let y = self.predict(log_n);
+        let x = self.model.feature(log_n as f64);
+        let fit_variance =
+            self.sigma.powi(2) * (1.0 / self.weight + (x - self.mean).powi(2) / self.sxx);
+        let noise_variance = (self.sigma * y).powi(2);
+        (y, 2.0 * (fit_variance + noise_variance).sqrt())
+    }
+
+    /// Returns the residual of `y` measured at `2^log_n` relative to the prediction.
+    pub fn deviation(&self, log_n: usize, y: f64) -> f64 {
+        y / self.predict(log_n) - 1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::report::fit::{Fit, Model};
+
+    #[test]
+    fn fit_models() {
+        let points = (10..16)
+            .map(|log_n| (log_n, 1.0 + 2.0 * (log_n as f64).exp2()))
+            .collect::<Vec<_>>();
+        let fit = Fit::best(&[Model::Linear, Model::NLogN], &points).unwrap();
+        assert_eq!(fit.model, Model::Linear);
+        assert!((fit.slope - 2.0).abs() < 1e-9 && fit.sigma < 1e-9);
+        let (value, error) = fit.extrapolate(20);
+        assert!((value / (1.0 + 2.0 * 2f64.powi(20)) - 1.0).abs() < 1e-9 && error < 1e-3);
+
+        let mut points = points;
+        points[3].1 *= 1.5;
+        let fit = Fit::new(Model::Linear, &points).unwrap();
+        assert!(fit.deviation(13, points[3].1) > 0.3);
+        assert!(Fit::new(Model::Linear, &points[..2]).is_none());
+    }
+}
@@ -0,0 +1,491 @@
+//! Markdown tables of [`Record`]s.
+
+use crate::{
+    report::{
+        fit::{self, Fit},
+        pareto_front, Record,
+    },
+    util::{human_size, human_throughput, human_time},
+    workload::Workload,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    time::Duration,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Column {
+    Time,
+    Throughput,
+    Absorbed,
+    Compress,
+    ProofSize,
+    PeakMem,
+    Verify,
+}
+
+impl Column {
+    pub const ALL: [Self; 7] = [
+        Self::Time,
+        Self::Throughput,
+        Self::Absorbed,
+        Self::Compress,
+        Self::ProofSize,
+        Self::PeakMem,
+        Self::Verify,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Self::Time => "time",
+            Self::Throughput => "throughput",
+            Self::Absorbed => "absorbed",
+            Self::Compress => "compress",
+            Self::ProofSize => "proof_size",
+            Self::PeakMem => "peak_mem",
+            Self::Verify => "verify",
+        }
+    }
+
+    pub fn value(self, record: &Record) -> Option<f64> {
+        let report = &record.report;
+        Some(match self {
+            Self::Time => report.time.as_secs_f64(),
+            Self::Throughput => report.throughput,
+            Self::Absorbed => report.absorbed_throughput,
+            Self::Compress => report.compression_time.as_secs_f64(),
+            Self::ProofSize => report.proof_size,
+            Self::PeakMem => report.peak_rss? as f64,
+            Self::Verify => report.verify_time?.as_secs_f64(),
+        })
+    }
+
+    pub fn format(self, value: f64) -> String {
+        match self {
+            Self::Time | Self::Compress | Self::Verify => {
+                human_time(Duration::from_secs_f64(value))
+            }
+            Self::Throughput => human_throughput(value),
+            Self::Absorbed => format!("{}/s", human_size(value)),
+            Self::ProofSize | Self::PeakMem => human_size(value),
+        }
+    }
+
+    fn cell(self, record: &Record) -> String {
+        self.value(record)
+            .map(|value| format!("`{}`", self.format(value)))
+            .unwrap_or_else(|| "`-`".to_string())
+    }
+}
+
+/// Filter of records by configuration.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub num_threads: Option<usize>,
+    pub log_inv_rate: Option<usize>,
+}
+
+impl Filter {
+    pub fn matches(&self, record: &Record) -> bool {
+        self.num_threads.is_none_or(|n| n == record.num_threads)
+            && self.log_inv_rate.is_none_or(|r| r == record.log_inv_rate)
+    }
+}
+
+/// Speedup of a record over the matching record of a reference backend.
+#[derive(Clone, Copy, Debug)]
+enum Speedup {
+    /// Proving time of the reference over the one of the record.
+    Ratio(f64),
+    /// More than one record of the reference backend matches, e.g. of different configs.
+    Ambiguous,
+}
+
+impl Speedup {
+    fn format(self) -> String {
+        match self {
+            Self::Ratio(ratio) => format!("`×{ratio:.2}`"),
+            Self::Ambiguous => "`×?`".to_string(),
+        }
+    }
+}
+
+/// Returns the speedup of `record` over the record of backend `reference` with the same
+/// permutation, configuration and PCS parameters. Records of the same hash label, which includes
+/// the config, are preferred, and the speedup is ambiguous if more than one record matches.
+fn speedup(records: &[&Record], record: &Record, reference: &str) -> Option<Speedup> {
+    let candidates = records
+        .iter()
+        .filter(|other| {
+            other.backend() == reference
+                && other.report.permutation == record.report.permutation
+                && other.report.workload == record.report.workload
+                && other.num_threads == record.num_threads
+                && other.log_inv_rate == record.log_inv_rate
+                && other.pcs == record.pcs
+                && other.log_permutations == record.log_permutations
+        })
+        .collect::<Vec<_>>();
+    let same_hash = candidates
+        .iter()
+        .filter(|other| other.hash == record.hash)
+        .copied()
+        .collect::<Vec<_>>();
+    let candidates = if same_hash.is_empty() {
+        candidates
+    } else {
+        same_hash
+    };
+    match candidates[..] {
+        [] => None,
+        [reference] => Some(Speedup::Ratio(
+            reference.report.time.as_secs_f64() / record.report.time.as_secs_f64(),
+        )),
+        _ => Some(Speedup::Ambiguous),
+    }
+}
+
+fn speedup_cell(records: &[&Record], record: &Record, reference: &str) -> String {
+    speedup(records, record, reference)
+        .map(Speedup::format)
+        .unwrap_or_else(|| "`-`".to_string())
+}
+
+/// Returns the overhead of linking permutations of [`Workload::Chains`] in `record`, i.e. its
+/// proving time over the one of independent permutations of the same backend, permutation and
+/// configuration, minus one.
+fn link_overhead(records: &[&Record], record: &Record) -> Option<f64> {
+    if !matches!(record.report.workload, Workload::Chains { .. }) {
+        return None;
+    }
+    let reference = records.iter().find(|other| {
+        other.backend() == record.backend()
+            && other.report.workload == Workload::Permutations
+            && other.report.permutation == record.report.permutation
+            && other.report.description == record.report.description
+            && other.num_threads == record.num_threads
+            && other.log_inv_rate == record.log_inv_rate
+            && other.log_permutations == record.log_permutations
+    })?;
+    Some(record.report.time.as_secs_f64() / reference.report.time.as_secs_f64() - 1.0)
+}
+
+fn link_overhead_cell(records: &[&Record], record: &Record) -> String {
+    link_overhead(records, record)
+        .map(|overhead| format!("`{:+.1}%`", 100.0 * overhead))
+        .unwrap_or_else(|| "`-`".to_string())
+}
+
+fn perm(log_permutations: usize) -> String {
+    format!("<code>2<sup>{log_permutations}</sup></code>")
+}
+
+fn row(cells: impl IntoIterator<Item = String>) -> String {
+    let cells = cells.into_iter().collect::<Vec<_>>();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn header(cells: impl IntoIterator<Item = String>) -> String {
+    let cells = cells.into_iter().collect::<Vec<_>>();
+    let separator = row(cells.iter().map(|_| "-".to_string()));
+    row(cells.into_iter().map(|cell| format!("`{cell}`"))) + &separator
+}
+
+/// Returns whether records have more than one thread count and rate respectively.
+fn varying(records: &[&Record]) -> (bool, bool) {
+    let num_threads = records.iter().map(|r| r.num_threads);
+    let log_inv_rates = records.iter().map(|r| r.log_inv_rate);
+    (
+        num_threads.collect::<BTreeSet<_>>().len() > 1,
+        log_inv_rates.collect::<BTreeSet<_>>().len() > 1,
+    )
+}
+
+/// Renders a table per backend with a row per hash and size.
+pub fn table(records: &[Record], filter: &Filter, reference: Option<&str>) -> String {
+    let records = records
+        .iter()
+        .filter(|r| filter.matches(r))
+        .collect::<Vec<_>>();
+    let (varying_threads, varying_rate) = varying(&records);
+
+    let mut backends = BTreeMap::<_, BTreeMap<_, Vec<_>>>::new();
+    for record in &records {
+        let hashes = backends.entry(record.backend()).or_default();
+        hashes
+            .entry(record.hash.as_str())
+            .or_default()
+            .push(*record);
+    }
+
+    let mut out = String::new();
+    for (backend, hashes) in backends {
+        let chains = (hashes.values().flatten())
+            .any(|r| matches!(r.report.workload, Workload::Chains { .. }));
+        let mut columns = vec!["hash".to_string()];
+        columns.extend(varying_threads.then(|| "threads".to_string()));
+        columns.extend(varying_rate.then(|| "rate".to_string()));
+        columns.push("perm".to_string());
+        columns.extend(Column::ALL.map(|c| c.header().to_string()));
+        columns.extend(reference.map(|reference| format!("speedup_over_{reference}")));
+        columns.extend(chains.then(|| "link_overhead".to_string()));
+
+        writeln!(out, "<!-- {backend} -->\n").unwrap();
+        let descriptions = hashes
+            .iter()
+            .flat_map(|(hash, rows)| {
+                let descriptions = rows.iter().filter_map(|r| r.report.description.as_ref());
+                descriptions.map(move |description| format!("- `{hash}`: {description}\n"))
+            })
+            .collect::<BTreeSet<_>>();
+        if !descriptions.is_empty() {
+            out.extend(descriptions);
+            out += "\n";
+        }
+        out += &header(columns.iter().cloned());
+        for (idx, (hash, mut rows)) in hashes.into_iter().enumerate() {
+            if idx != 0 {
+                out += &row(columns.iter().map(|_| String::new()));
+            }
+            rows.sort_by_key(|r| (r.num_threads, r.log_inv_rate, r.log_permutations));
+            for record in rows {
+                let mut cells = vec![format!("`{hash}`")];
+                cells.extend(varying_threads.then(|| format!("`{}`", record.num_threads)));
+                cells.extend(varying_rate.then(|| format!("`1/{}`", 1 << record.log_inv_rate)));
+                cells.push(perm(record.log_permutations));
+                cells.extend(Column::ALL.map(|c| c.cell(record)));
+                cells.extend(reference.map(|reference| speedup_cell(&records, record, reference)));
+                cells.extend(chains.then(|| link_overhead_cell(&records, record)));
+                out += &row(cells);
+            }
+        }
+        out += "\n";
+    }
+    out
+}
+
+/// Renders a table per permutation and configuration with a row per size and a column per
+/// backend, to compare implementations of the same permutation side by side.
+pub fn pivot(
+    records: &[Record],
+    filter: &Filter,
+    column: Column,
+    reference: Option<&str>,
+) -> String {
+    let records = records
+        .iter()
+        .filter(|r| filter.matches(r))
+        .collect::<Vec<_>>();
+
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for record in &records {
+        let key = (
+            record.report.permutation.as_str(),
+            record.num_threads,
+            record.log_inv_rate,
+        );
+        groups.entry(key).or_default().push(*record);
+    }
+
+    let mut out = String::new();
+    for ((permutation, num_threads, log_inv_rate), group) in groups {
+        let implementations = group
+            .iter()
+            .map(|r| (r.backend(), r.hash.as_str()))
+            .collect::<BTreeSet<_>>();
+        let label = |(backend, hash): (&str, &str)| {
+            let ambiguous = implementations
+                .iter()
+                .filter(|(b, _)| *b == backend)
+                .count()
+                > 1;
+            if ambiguous {
+                format!("{backend}/{hash}")
+            } else {
+                backend.to_string()
+            }
+        };
+        let sizes = group
+            .iter()
+            .map(|r| r.log_permutations)
+            .collect::<BTreeSet<_>>();
+
+        writeln!(
+            out,
+            "<!-- {permutation}, {num_threads} threads, rate 1/{}, {} -->\n",
+            1 << log_inv_rate,
+            column.header(),
+        )
+        .unwrap();
+        out += &header(
+            ["perm".to_string()].into_iter().chain(
+                implementations
+                    .iter()
+                    .map(|implementation| label(*implementation)),
+            ),
+        );
+        for log_permutations in sizes {
+            let cells = implementations.iter().map(|(backend, hash)| {
+                let Some(record) = group.iter().find(|r| {
+                    r.backend() == *backend
+                        && r.hash == *hash
+                        && r.log_permutations == log_permutations
+                }) else {
+                    return "`-`".to_string();
+                };
+                let cell = column.cell(record);
+                match reference.and_then(|reference| speedup(&records, record, reference)) {
+                    Some(speedup) => format!("{cell} {}", speedup.format()),
+                    None => cell,
+                }
+            });
+            out += &row([perm(log_permutations)].into_iter().chain(cells));
+        }
+        out += "\n";
+    }
+    out
+}
+
+/// Renders a table per backend, hash, thread count and size with a row per PCS configuration on
+/// the Pareto front of proving time, verifying time and proof size.
+pub fn pareto(records: &[Record], filter: &Filter) -> String {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for record in records.iter().filter(|r| filter.matches(r)) {
+        let key = (
+            record.backend(),
+            record.hash.as_str(),
+            record.num_threads,
+            record.log_permutations,
+        );
+        groups.entry(key).or_default().push(record);
+    }
+
+    let mut out = String::new();
+    for ((backend, hash, num_threads, log_permutations), group) in groups {
+        let front = pareto_front(&group);
+        writeln!(
+            out,
+            "<!-- {backend} {hash}, {num_threads} threads, 2^{log_permutations} permutations, \
+             {} of {} configurations -->\n",
+            front.len(),
+            group.len(),
+        )
+        .unwrap();
+        let columns = [Column::Time, Column::Verify, Column::ProofSize];
+        out += &header(
+            ["rate", "security", "pow", "final_poly"]
+                .map(str::to_string)
+                .into_iter()
+                .chain(columns.map(|c| c.header().to_string())),
+        );
+        for record in front {
+            let pcs = record.pcs;
+            let optional = |value: Option<usize>| {
+                value.map_or_else(|| "`-`".to_string(), |value| format!("`{value}`"))
+            };
+            let mut cells = vec![
+                format!("`1/{}`", 1 << record.log_inv_rate),
+                optional(pcs.and_then(|pcs| pcs.security_bits)),
+                optional(pcs.map(|pcs| pcs.pow_bits)),
+                optional(
+                    pcs.and_then(|pcs| pcs.log_final_poly_len)
+                        .map(|len| 1 << len),
+                ),
+            ];
+            cells.extend(columns.map(|c| c.cell(record)));
+            out += &row(cells);
+        }
+        out += "\n";
+    }
+    out
+}
+
+/// Renders a table per backend, hash and configuration with a row per quantity of
+/// [`fit::QUANTITIES`], showing the best fitting model, its fit quality, predictions at
+/// `2^log_n` for each of `extrapolate`, and sizes deviating from the fit by more than `deviation`.
+pub fn fits(records: &[Record], filter: &Filter, extrapolate: &[usize], deviation: f64) -> String {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for record in records.iter().filter(|r| filter.matches(r)) {
+        let key = (
+            record.backend(),
+            record.hash.as_str(),
+            record.num_threads,
+            record.log_inv_rate,
+            record.pcs,
+        );
+        groups.entry(key).or_default().push(record);
+    }
+
+    let mut out = String::new();
+    for ((backend, hash, num_threads, log_inv_rate, pcs), group) in groups {
+        let rows = fit::QUANTITIES
+            .iter()
+            .filter_map(|(column, models)| {
+                let points = group
+                    .iter()
+                    .filter_map(|r| Some((r.log_permutations, column.value(r)?)))
+                    .collect::<Vec<_>>();
+                Some((column, Fit::best(models, &points)?, points))
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            continue;
+        }
+
+        write!(
+            out,
+            "<!-- {backend} {hash}, {num_threads} threads, rate 1/{}",
+            1 << log_inv_rate,
+        )
+        .unwrap();
+        if let Some(pcs) = pcs {
+            if let Some(security_bits) = pcs.security_bits {
+                write!(out, ", {security_bits} security bits").unwrap();
+            }
+            if let Some(num_queries) = pcs.num_queries {
+                write!(out, ", {num_queries} queries").unwrap();
+            }
+            write!(out, ", {} pow bits", pcs.pow_bits).unwrap();
+            if let Some(log_final_poly_len) = pcs.log_final_poly_len {
+                write!(out, ", final poly {}", 1 << log_final_poly_len).unwrap();
+            }
+        }
+        writeln!(out, " -->\n").unwrap();
+        out += &header(
+            ["quantity", "model", "error", "r2"]
+                .map(str::to_string)
+                .into_iter()
+                .chain(extrapolate.iter().map(|log_n| format!("2^{log_n}")))
+                .chain(["deviations".to_string()]),
+        );
+        for (column, fit, points) in rows {
+            let mut cells = vec![
+                format!("`{}`", column.header()),
+                format!("`{}`", fit.model.name()),
+                format!("`±{:.1}%`", fit.sigma * 100.0),
+                format!("`{:.4}`", fit.r_squared),
+            ];
+            cells.extend(extrapolate.iter().map(|log_n| {
+                let (value, error) = fit.extrapolate(*log_n);
+                format!("`{} ± {}`", column.format(value), column.format(error))
+            }));
+            let deviations = points
+                .iter()
+                .filter_map(|(log_n, value)| {
+                    let residual = fit.deviation(*log_n, *value);
+                    (residual.abs() > deviation)
+                        .then(|| format!("{} `{:+.1}%`", perm(*log_n), residual * 100.0))
+                })
+                .collect::<Vec<_>>();
+            cells.push(if deviations.is_empty() {
+                "`-`".to_string()
+            } else {
+                deviations.join(", ")
+            });
+            out += &row(cells);
+        }
+        out += "\n";
+    }
+    out
+}
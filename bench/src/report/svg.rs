@@ -0,0 +1,247 @@
+//! Log-log SVG charts of [`Record`]s against the number of permutations.
+
+use crate::report::{
+    markdown::{Column, Filter},
+    Record,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+/// Columns charted by [`charts`].
+pub const COLUMNS: [Column; 4] = [
+    Column::Throughput,
+    Column::ProofSize,
+    Column::PeakMem,
+    Column::Verify,
+];
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 420.0;
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 200.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 50.0;
+
+const COLORS: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// Chart of a column of records with the same number of threads.
+#[derive(Clone, Debug)]
+pub struct Chart {
+    pub column: Column,
+    pub num_threads: usize,
+    pub svg: String,
+}
+
+impl Chart {
+    /// Returns file name of the chart, e.g. `t24_throughput.svg`.
+    pub fn file_name(&self) -> String {
+        format!("t{}_{}.svg", self.num_threads, self.column.header())
+    }
+}
+
+/// Renders a chart per column of [`COLUMNS`] and number of threads, with a series per backend
+/// and hash. Columns without any value are skipped.
+pub fn charts(records: &[Record], filter: &Filter) -> Vec<Chart> {
+    let mut threads = BTreeMap::<_, Vec<_>>::new();
+    for record in records.iter().filter(|r| filter.matches(r)) {
+        threads.entry(record.num_threads).or_default().push(record);
+    }
+
+    let mut charts = Vec::new();
+    for (num_threads, records) in threads {
+        let varying_rate = records
+            .iter()
+            .map(|r| r.log_inv_rate)
+            .collect::<BTreeSet<_>>()
+            .len()
+            > 1;
+        for column in COLUMNS {
+            let mut series = BTreeMap::<_, Vec<_>>::new();
+            for record in &records {
+                let Some(value) = column.value(record).filter(|v| *v > 0.0) else {
+                    continue;
+                };
+                let mut label = format!("{}/{}", record.backend(), record.hash);
+                if varying_rate {
+                    label += &format!(" 1/{}", 1 << record.log_inv_rate);
+                }
+                series
+                    .entry(label)
+                    .or_default()
+                    .push((record.log_permutations, value));
+            }
+            if series.is_empty() {
+                continue;
+            }
+            series
+                .values_mut()
+                .for_each(|points| points.sort_by_key(|p| p.0));
+            let title = format!("{}, {num_threads} threads", column.header());
+            charts.push(Chart {
+                column,
+                num_threads,
+                svg: chart(&title, column, &series),
+            });
+        }
+    }
+    charts
+}
+
+/// Renders a log-log chart of `series` of points `(log_permutations, value)`.
+fn chart(title: &str, column: Column, series: &BTreeMap<String, Vec<(usize, f64)>>) -> String {
+    let points = series.values().flatten();
+    let (x_min, x_max) = points
+        .clone()
+        .fold((usize::MAX, 0), |(lo, hi), (x, _)| (lo.min(*x), hi.max(*x)));
+    let (y_min, y_max) = points.fold((f64::INFINITY, 0.0f64), |(lo, hi), (_, y)| {
+        (lo.min(y.log10()), hi.max(y.log10()))
+    });
+    let (x_min, x_max) = if x_min == x_max {
+        (x_min.saturating_sub(1), x_max + 1)
+    } else {
+        (x_min, x_max)
+    };
+    let (y_min, y_max) = (y_min.floor(), y_max.ceil().max(y_min.floor() + 1.0));
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x = |log_permutations: usize| {
+        MARGIN_LEFT + (log_permutations - x_min) as f64 / (x_max - x_min) as f64 * plot_width
+    };
+    let y = |value: f64| {
+        MARGIN_TOP + plot_height - (value.log10() - y_min) / (y_max - y_min) * plot_height
+    };
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-size="14">{}</text>"#,
+        MARGIN_LEFT + plot_width / 2.0,
+        MARGIN_TOP / 2.0 + 5.0,
+        escape(title)
+    )
+    .unwrap();
+
+    for log_permutations in x_min..=x_max {
+        let x = x(log_permutations);
+        writeln!(
+            out,
+            r##"<line x1="{x:.1}" y1="{MARGIN_TOP}" x2="{x:.1}" y2="{:.1}" stroke="#ddd"/>"##,
+            MARGIN_TOP + plot_height
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<text x="{x:.1}" y="{:.1}" text-anchor="middle">2<tspan dy="-5" font-size="9">{log_permutations}</tspan></text>"#,
+            MARGIN_TOP + plot_height + 18.0
+        )
+        .unwrap();
+    }
+    for value in y_ticks(y_min, y_max) {
+        let y = y(value);
+        writeln!(
+            out,
+            r##"<line x1="{MARGIN_LEFT}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="#ddd"/>"##,
+            MARGIN_LEFT + plot_width
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            MARGIN_LEFT - 6.0,
+            y + 4.0,
+            escape(&column.format(value))
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        r#"<rect x="{MARGIN_LEFT}" y="{MARGIN_TOP}" width="{plot_width}" height="{plot_height}" fill="none" stroke="black"/>"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">permutations</text>"#,
+        MARGIN_LEFT + plot_width / 2.0,
+        HEIGHT - 10.0
+    )
+    .unwrap();
+
+    for (idx, (label, points)) in series.iter().enumerate() {
+        let color = COLORS[idx % COLORS.len()];
+        let polyline = points
+            .iter()
+            .map(|(log_permutations, value)| {
+                format!("{:.1},{:.1}", x(*log_permutations), y(*value))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            out,
+            r#"<polyline points="{polyline}" fill="none" stroke="{color}" stroke-width="2"/>"#
+        )
+        .unwrap();
+        for (log_permutations, value) in points {
+            writeln!(
+                out,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{color}"><title>{}: {}</title></circle>"#,
+                x(*log_permutations),
+                y(*value),
+                escape(label),
+                escape(&column.format(*value))
+            )
+            .unwrap();
+        }
+
+        let legend_x = MARGIN_LEFT + plot_width + 16.0;
+        let legend_y = MARGIN_TOP + 10.0 + idx as f64 * 18.0;
+        writeln!(
+            out,
+            r#"<line x1="{legend_x:.1}" y1="{legend_y:.1}" x2="{:.1}" y2="{legend_y:.1}" stroke="{color}" stroke-width="2"/>"#,
+            legend_x + 20.0
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}">{}</text>"#,
+            legend_x + 26.0,
+            legend_y + 4.0,
+            escape(label)
+        )
+        .unwrap();
+    }
+
+    out += "</svg>\n";
+    out
+}
+
+/// Returns tick values between decades `10^lo` and `10^hi`, with intermediate ticks at 2 and 5
+/// when the range spans few decades.
+fn y_ticks(lo: f64, hi: f64) -> Vec<f64> {
+    let mantissas: &[f64] = if hi - lo <= 2.0 {
+        &[1.0, 2.0, 5.0]
+    } else {
+        &[1.0]
+    };
+    (lo as i32..=hi as i32)
+        .flat_map(|exp| mantissas.iter().map(move |m| m * 10f64.powi(exp)))
+        .filter(|value| value.log10() <= hi)
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,445 @@
+//! Harness as a library, to benchmark backends from other tools and observe runs as they progress.
+//! The CLI of [`main!`](crate::main) and the [`criterion`](crate::criterion) helpers wrap it.
+
+use crate::{
+    alloc, check, cold_start, cost, hash::HashDescriptor, input::InputSource, preflight,
+    report::Report, routine, sample, stats, timeline, util::peak_rss, warm_up, BenchConfig,
+    HashInSnark,
+};
+use core::{hint::black_box, marker::PhantomData};
+use std::time::{Duration, Instant};
+
+/// Phases of a run of an instance, passed to [`Runner::on_phase`] as each is entered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Setting up an instance, by [`Runner::run`], [`Runner::test`] and [`Runner::instances`].
+    Setup,
+    /// Setting up an instance and generating its first proof, by [`Runner::bench`].
+    ColdStart,
+    WarmUp,
+    /// Sampling proving time.
+    Sample,
+    /// Sampling verifying time.
+    Verify,
+    /// Counting hashing of a verification.
+    VerifierCost,
+    /// Sampling heap and RSS during an extra proof.
+    Timeline,
+    /// Generating a proof without measuring it, by [`Runner::run`] and [`Runner::test`].
+    Prove,
+}
+
+/// Instance set up, passed to [`Runner::on_setup`].
+#[derive(Clone, Debug)]
+pub struct Setup {
+    pub num_permutations: usize,
+    /// Time of `HashInSnark::new`.
+    pub time: Duration,
+}
+
+/// Proving sample of [`Runner::bench`], passed to [`Runner::on_sample`].
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub num_permutations: usize,
+    pub index: usize,
+    pub time: Duration,
+    pub proof_size: usize,
+    /// Peak heap allocated by the sample, on top of the heap allocated before sampling.
+    pub peak_heap: Option<usize>,
+}
+
+/// Metrics of [`Runner::bench`] besides proving time, proof size and memory, which can be skipped
+/// to save time.
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    /// Samples verifying time.
+    pub verify_time: bool,
+    /// Counts hashing of a verification.
+    pub verifier_cost: bool,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            verify_time: true,
+            verifier_cost: true,
+        }
+    }
+}
+
+type Hook<'a, T> = Box<dyn FnMut(&T) + 'a>;
+
+#[derive(Default)]
+struct Hooks<'a> {
+    setup: Vec<Hook<'a, Setup>>,
+    sample: Vec<Hook<'a, Sample>>,
+    phase: Vec<Hook<'a, Phase>>,
+    verify: Vec<Hook<'a, Result<(), String>>>,
+}
+
+impl Hooks<'_> {
+    fn setup(&mut self, setup: &Setup) {
+        self.setup.iter_mut().for_each(|f| f(setup));
+    }
+
+    fn sample(&mut self, sample: &Sample) {
+        self.sample.iter_mut().for_each(|f| f(sample));
+    }
+
+    fn phase(&mut self, phase: Phase) {
+        self.phase.iter_mut().for_each(|f| f(&phase));
+    }
+
+    fn verified(&mut self, result: &Result<(), String>) {
+        self.verify.iter_mut().for_each(|f| f(result));
+    }
+
+    /// Verifies the deserialized proof of `bytes`, returning the error if it's invalid.
+    fn verify<H: HashInSnark>(&mut self, snark: &H, bytes: &[u8]) -> Option<String> {
+        let result = check(snark, bytes);
+        self.verified(&result);
+        result.err()
+    }
+}
+
+/// Builder of runs of instances of `H` of each size in turn.
+///
+/// ```ignore
+/// let reports = Runner::<H>::new()
+///     .sizes([1 << 10, 1 << 12])
+///     .seed(0)
+///     .on_sample(|sample| eprintln!("{sample:?}"))
+///     .bench();
+/// ```
+pub struct Runner<'a, H> {
+    sizes: Vec<usize>,
+    config: BenchConfig,
+    seed: Option<u64>,
+    metrics: Metrics,
+    hooks: Hooks<'a>,
+    _snark: PhantomData<fn() -> H>,
+}
+
+impl<H: HashInSnark> Default for Runner<'_, H> {
+    fn default() -> Self {
+        Self {
+            sizes: Vec::new(),
+            config: BenchConfig::default(),
+            seed: None,
+            metrics: Metrics::default(),
+            hooks: Hooks::default(),
+            _snark: PhantomData,
+        }
+    }
+}
+
+impl<'a, H: HashInSnark> Runner<'a, H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Numbers of permutations of the instances.
+    pub fn sizes(mut self, sizes: impl IntoIterator<Item = usize>) -> Self {
+        self.sizes = sizes.into_iter().collect();
+        self
+    }
+
+    pub fn config(mut self, config: BenchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Seeds random inputs of each instance, so runs prove the same inputs. Bytes installed by
+    /// [`input::init`](crate::input::init) take precedence.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn on_setup(mut self, f: impl FnMut(&Setup) + 'a) -> Self {
+        self.hooks.setup.push(Box::new(f));
+        self
+    }
+
+    pub fn on_sample(mut self, f: impl FnMut(&Sample) + 'a) -> Self {
+        self.hooks.sample.push(Box::new(f));
+        self
+    }
+
+    pub fn on_phase(mut self, mut f: impl FnMut(Phase) + 'a) -> Self {
+        self.hooks.phase.push(Box::new(move |phase| f(*phase)));
+        self
+    }
+
+    /// Observes the result of verifying each proof, after a roundtrip through serialization
+    /// except for the proof of [`Runner::test`] verified before it.
+    pub fn on_verify(mut self, f: impl FnMut(&Result<(), String>) + 'a) -> Self {
+        self.hooks.verify.push(Box::new(f));
+        self
+    }
+
+    fn rng(&self) -> InputSource {
+        self.seed
+            .map_or_else(InputSource::new, InputSource::with_seed)
+    }
+
+    fn setup(&mut self, num_permutations: usize) -> H {
+        self.hooks.phase(Phase::Setup);
+        let start = Instant::now();
+        let snark = tracing::info_span!("setup").in_scope(|| H::new(num_permutations));
+        self.hooks.setup(&Setup {
+            num_permutations: snark.num_permutations(),
+            time: start.elapsed(),
+        });
+        snark
+    }
+
+    /// Generates a proof of each instance without measuring, e.g. to be traced or profiled.
+    pub fn run(&mut self) {
+        for num_permutations in self.sizes.clone() {
+            let snark = self.setup(num_permutations);
+            let input = tracing::info_span!("generate_input")
+                .in_scope(|| black_box(snark.generate_input(self.rng())));
+            self.hooks.phase(Phase::Prove);
+            let proof = tracing::info_span!("prove").in_scope(|| snark.prove(input));
+            drop(black_box(proof));
+        }
+    }
+
+    /// Verifies a proof of each instance before and after a roundtrip through serialization,
+    /// returning the first error.
+    pub fn test(&mut self) -> Result<(), H::Error> {
+        for num_permutations in self.sizes.clone() {
+            let snark = self.setup(num_permutations);
+            self.hooks.phase(Phase::Prove);
+            let proof = snark.prove(snark.generate_input(self.rng()));
+            let bytes = H::serialize_proof(&proof);
+            for proof in [proof, H::deserialize_proof(&bytes)] {
+                let result = snark.verify(&proof);
+                let outcome = result.as_ref().map(|_| ());
+                self.hooks
+                    .verified(&outcome.map_err(|err| format!("{err:?}")));
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets up each instance and passes it with a source of its inputs to `f`, to be measured by
+    /// other harnesses.
+    pub fn instances(&mut self, mut f: impl FnMut(&H, &mut InputSource)) {
+        for num_permutations in self.sizes.clone() {
+            let snark = self.setup(num_permutations);
+            f(&snark, &mut self.rng());
+        }
+    }
+
+    /// Benchmarks each instance, returning its report.
+    pub fn bench(&mut self) -> Vec<Report> {
+        self.sizes
+            .clone()
+            .into_iter()
+            .map(|num_permutations| self.bench_instance(num_permutations))
+            .collect()
+    }
+
+    fn bench_instance(&mut self, num_permutations: usize) -> Report {
+        let mut rng = self.rng();
+        let hooks = &mut self.hooks;
+
+        hooks.phase(Phase::ColdStart);
+        let (snark, cold, bytes) = cold_start::<H>(num_permutations, &mut rng);
+        let num_permutations = snark.num_permutations();
+        hooks.setup(&Setup {
+            num_permutations,
+            time: cold.setup_time,
+        });
+        let mut verify_failures = Vec::from_iter(hooks.verify(&snark, &bytes));
+
+        hooks.phase(Phase::WarmUp);
+        warm_up(&snark, &self.config, &mut rng);
+
+        // Each proof is verified after its peak heap is taken, and the peak is reset to exclude the
+        // verification.
+        hooks.phase(Phase::Sample);
+        let baseline = alloc::reset_peak();
+        let mut peak = baseline;
+        let mut total_proof_size = 0;
+        let mut index = 0;
+        let (samples, ci) = sample(&self.config, || {
+            let (elapsed, bytes) = routine(&snark, &mut rng);
+            let sample_peak = alloc::peak();
+            peak = peak.max(sample_peak);
+            hooks.sample(&Sample {
+                num_permutations,
+                index,
+                time: elapsed,
+                proof_size: bytes.len(),
+                peak_heap: alloc::is_tracking().then(|| sample_peak.saturating_sub(baseline)),
+            });
+            index += 1;
+            total_proof_size += bytes.len();
+            verify_failures.extend(hooks.verify(&snark, &bytes));
+            drop(bytes);
+            alloc::reset_peak();
+            elapsed
+        });
+        let peak_heap = alloc::is_tracking().then(|| peak.saturating_sub(baseline));
+
+        let (_, bytes) = routine(&snark, &mut rng);
+        verify_failures.extend(hooks.verify(&snark, &bytes));
+        let proof = H::deserialize_proof(&bytes);
+        let verify_time = self.metrics.verify_time.then(|| {
+            hooks.phase(Phase::Verify);
+            let (verify_samples, _) = sample(&self.config, || {
+                let start = Instant::now();
+                black_box(snark.verify(black_box(&proof))).ok();
+                start.elapsed()
+            });
+            Duration::from_secs_f64(stats::median(&verify_samples))
+        });
+        let verifier_cost = self
+            .metrics
+            .verifier_cost
+            .then(|| {
+                hooks.phase(Phase::VerifierCost);
+                cost::measure(|| snark.verify(&proof).ok()).1
+            })
+            .filter(|cost| *cost != Default::default());
+
+        let timeline = self.config.timeline_interval.map(|interval| {
+            hooks.phase(Phase::Timeline);
+            timeline(&snark, interval, &mut rng)
+        });
+
+        let time = Duration::from_secs_f64(stats::median(&samples));
+        let throughput = num_permutations as f64 / time.as_secs_f64();
+        let proof_size = total_proof_size as f64 / samples.len() as f64;
+        Report {
+            permutation: H::Hash::NAME.to_string(),
+            workload: snark.workload(),
+            num_permutations,
+            time,
+            throughput,
+            absorbed_throughput: throughput * H::Hash::rate_bytes(),
+            compression_time: time
+                .div_f64((snark.workload()).compressions::<H::Hash>(num_permutations)),
+            proof_size,
+            verify_time,
+            verifier_cost,
+            num_samples: samples.len(),
+            verify_failures,
+            ci,
+            cold,
+            peak_heap,
+            peak_rss: peak_rss(),
+            timeline,
+            description: Some(snark.describe()),
+            cpus: preflight::pinned().map(<[_]>::to_vec),
+            noise: preflight::warnings().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        codec::Codec,
+        hash::KeccakF1600,
+        report::Description,
+        runner::{Metrics, Phase, Runner},
+        BenchConfig, HashInSnark,
+    };
+    use rand::RngCore;
+    use std::{cell::RefCell, time::Duration};
+
+    struct Sum(usize);
+
+    impl HashInSnark for Sum {
+        type Hash = KeccakF1600;
+        type Input = Vec<u64>;
+        type Proof = u64;
+        type Error = ();
+
+        fn new(num_permutations: usize) -> Self {
+            Self(num_permutations)
+        }
+
+        fn num_permutations(&self) -> usize {
+            self.0
+        }
+
+        fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+            (0..self.0).map(|_| rng.next_u64() >> 8).collect()
+        }
+
+        fn prove(&self, input: Self::Input) -> Self::Proof {
+            input.iter().sum::<u64>() % 3
+        }
+
+        fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error> {
+            (*proof < 3).then_some(()).ok_or(())
+        }
+
+        fn field_bits() -> u32 {
+            64
+        }
+
+        fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+            codec.encode(proof)
+        }
+
+        fn decode_proof(bytes: &[u8], codec: Codec) -> Self::Proof {
+            codec.decode(bytes)
+        }
+
+        fn describe(&self) -> Description {
+            Description::default()
+        }
+    }
+
+    #[test]
+    fn observe_bench() {
+        let config = BenchConfig {
+            warm_up_time: Duration::ZERO,
+            min_samples: 3,
+            max_samples: 3,
+            ..Default::default()
+        };
+        let phases = RefCell::new(Vec::new());
+        let (mut setups, mut samples, mut verified) = (0, 0, 0);
+        let reports = Runner::<Sum>::new()
+            .sizes([4, 8])
+            .config(config)
+            .seed(1)
+            .metrics(Metrics {
+                verify_time: false,
+                ..Default::default()
+            })
+            .on_phase(|phase| phases.borrow_mut().push(phase))
+            .on_setup(|_| setups += 1)
+            .on_sample(|sample| samples += sample.index)
+            .on_verify(|result| verified += result.is_ok() as usize)
+            .bench();
+
+        assert_eq!(reports.len(), 2);
+        assert!(reports
+            .iter()
+            .all(|r| r.verify_time.is_none() && r.num_samples == 3));
+        assert_eq!((setups, samples, verified), (2, 2 * (1 + 2), 2 * 5));
+        assert_eq!(
+            phases.borrow()[..4],
+            [
+                Phase::ColdStart,
+                Phase::WarmUp,
+                Phase::Sample,
+                Phase::VerifierCost
+            ]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Repeated proving for a long duration, to detect memory growth and latency drift across proofs,
+//! e.g. from leaked or fragmented memory of state reused by an instance.
+
+use crate::{
+    alloc,
+    hash::HashDescriptor,
+    input::InputSource,
+    report::Description,
+    stats,
+    timeline::rss,
+    util::{human_size, human_time},
+    HashInSnark,
+};
+use core::{
+    fmt::{self, Display},
+    hint::black_box,
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct SoakConfig {
+    /// Duration of proving, after which the running proof is the last.
+    pub duration: Duration,
+    /// Relative growth of memory or latency between the first and last third of iterations
+    /// above which it is flagged.
+    pub threshold: f64,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(600),
+            threshold: 0.1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Iteration {
+    /// Time since the soak started, at the end of the iteration.
+    pub time: Duration,
+    pub latency: Duration,
+    /// Bytes allocated after the proof is dropped, if [`alloc::TrackingAllocator`] is installed.
+    pub heap: Option<usize>,
+    /// Resident set size after the proof is dropped, only available on Linux.
+    pub rss: Option<usize>,
+}
+
+/// Change of a quantity over the iterations but the first, which pays one-time costs.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Trend {
+    /// Median of the first third of iterations.
+    pub first: f64,
+    /// Median of the last third of iterations.
+    pub last: f64,
+    /// Slope of the least squares line per iteration.
+    pub slope: f64,
+}
+
+impl Trend {
+    /// Returns the trend of `values`, or `None` if there are too few to split into thirds.
+    pub fn new(values: &[f64]) -> Option<Self> {
+        let values = values.get(1..)?;
+        let third = values.len() / 3;
+        if third == 0 {
+            return None;
+        }
+        let xs = (0..values.len()).map(|x| x as f64).collect::<Vec<_>>();
+        let (slope, _) = stats::linear_fit(&xs, values);
+        Some(Self {
+            first: stats::median(&values[..third]),
+            last: stats::median(&values[values.len() - third..]),
+            slope,
+        })
+    }
+
+    /// Returns the growth from the first to the last third relative to the first, infinite if it
+    /// grew from nothing.
+    pub fn growth(&self) -> f64 {
+        if self.first <= 0.0 {
+            return if self.last > self.first {
+                f64::INFINITY
+            } else {
+                0.0
+            };
+        }
+        (self.last - self.first) / self.first
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SoakReport {
+    /// Name of the permutation, i.e. `HashDescriptor::NAME`.
+    pub permutation: String,
+    pub description: Description,
+    pub num_permutations: usize,
+    pub threshold: f64,
+    pub iterations: Vec<Iteration>,
+    pub latency: Option<Trend>,
+    pub heap: Option<Trend>,
+    pub rss: Option<Trend>,
+}
+
+impl SoakReport {
+    /// Returns descriptions of trends growing beyond the threshold.
+    pub fn flags(&self) -> Vec<String> {
+        [
+            ("latency", self.latency),
+            ("heap", self.heap),
+            ("rss", self.rss),
+        ]
+        .into_iter()
+        .filter_map(|(name, trend)| {
+            let growth = trend?.growth();
+            (growth > self.threshold).then(|| {
+                format!(
+                    "{name} grew by {:.1}%, over the threshold of {:.1}%",
+                    growth * 100.0,
+                    self.threshold * 100.0
+                )
+            })
+        })
+        .collect()
+    }
+}
+
+impl Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "    system: {}", self.description)?;
+        let elapsed = self.iterations.last().map(|it| it.time).unwrap_or_default();
+        write!(
+            f,
+            "      soak: {} proofs in {}",
+            self.iterations.len(),
+            human_time(elapsed)
+        )?;
+        let time = |secs: f64| human_time(Duration::from_secs_f64(secs.max(0.0)));
+        let trends: [(_, _, &dyn Fn(f64) -> String); 3] = [
+            ("   latency", self.latency, &time),
+            ("      heap", self.heap, &human_size),
+            ("       rss", self.rss, &human_size),
+        ];
+        for (name, trend, format) in trends {
+            let Some(trend) = trend else {
+                continue;
+            };
+            let sign = if trend.slope < 0.0 { "-" } else { "+" };
+            write!(
+                f,
+                "\n{name}: {} -> {} ({:+.2}%), {sign}{} per proof",
+                format(trend.first),
+                format(trend.last),
+                trend.growth() * 100.0,
+                format(trend.slope.abs()),
+            )?;
+        }
+        for flag in self.flags() {
+            write!(f, "\n   warning: {flag}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Proves repeatedly with a single instance for `config.duration`, recording latency, heap and RSS
+/// after each proof.
+pub fn soak<H: HashInSnark>(num_permutations: usize, config: &SoakConfig) -> SoakReport {
+    let snark = H::new(num_permutations);
+    let mut rng = InputSource::new();
+
+    let start = Instant::now();
+    let mut iterations = Vec::new();
+    while iterations.is_empty() || start.elapsed() < config.duration {
+        let input = black_box(snark.generate_input(&mut rng));
+        let proof_start = Instant::now();
+        let proof = snark.prove(input);
+        let latency = proof_start.elapsed();
+        drop(black_box(proof));
+        // Reserve before measuring, so the recorded iterations can be excluded from the heap.
+        iterations.reserve(1);
+        let recorded = iterations.capacity() * size_of::<Iteration>();
+        iterations.push(Iteration {
+            time: start.elapsed(),
+            latency,
+            heap: alloc::is_tracking().then(|| alloc::current().saturating_sub(recorded)),
+            rss: rss(),
+        });
+    }
+
+    let trend = |f: fn(&Iteration) -> Option<f64>| {
+        let values = iterations.iter().map(f).collect::<Option<Vec<_>>>()?;
+        Trend::new(&values)
+    };
+    SoakReport {
+        permutation: H::Hash::NAME.to_string(),
+        description: snark.describe(),
+        num_permutations: snark.num_permutations(),
+        threshold: config.threshold,
+        latency: trend(|it| Some(it.latency.as_secs_f64())),
+        heap: trend(|it| it.heap.map(|heap| heap as f64)),
+        rss: trend(|it| it.rss.map(|rss| rss as f64)),
+        iterations,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::soak::{SoakReport, Trend};
+
+    #[test]
+    fn trend() {
+        let values = [10.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 3.0];
+        let trend = Trend::new(&values).unwrap();
+        assert_eq!((trend.first, trend.last), (1.0, 3.0));
+        assert!((trend.slope - 0.3).abs() < 1e-9);
+        assert_eq!(trend.growth(), 2.0);
+        assert!(Trend::new(&values[..3]).is_none());
+
+        let trend = |first, last| Trend {
+            first,
+            last,
+            slope: 0.0,
+        };
+        assert_eq!(trend(0.0, 0.0).growth(), 0.0);
+        assert_eq!(trend(0.0, 1.0).growth(), f64::INFINITY);
+    }
+
+    #[test]
+    fn flags() {
+        let trend = |first, last| {
+            Some(Trend {
+                first,
+                last,
+                slope: 0.0,
+            })
+        };
+        let report = SoakReport {
+            permutation: String::new(),
+            description: Default::default(),
+            num_permutations: 1,
+            threshold: 0.1,
+            iterations: Vec::new(),
+            latency: trend(1.0, 1.05),
+            heap: trend(0.0, 1.0),
+            rss: trend(2.0, 3.0),
+        };
+        let flags = report.flags();
+        assert_eq!(flags.len(), 2);
+        assert!(flags[0].starts_with("heap grew"));
+        assert!(flags[1].starts_with("rss grew by 50.0%"));
+    }
+}
@@ -0,0 +1,156 @@
+//! Distribution-free statistics over benchmark samples.
+
+/// Two-sided z-score of 95% confidence.
+pub const Z_95: f64 = 1.959964;
+
+/// Returns the median of `samples`, which must be non-empty.
+pub fn median(samples: &[f64]) -> f64 {
+    let sorted = sorted(samples);
+    let n = sorted.len();
+    (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0
+}
+
+/// Returns the `q`-quantile of `samples` by linear interpolation between order statistics, where
+/// `samples` must be non-empty and `q` in `[0, 1]`.
+pub fn quantile(samples: &[f64], q: f64) -> f64 {
+    let sorted = sorted(samples);
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Returns the slope and intercept of the least squares line through `(xs[i], ys[i])`, where
+/// `xs` must have at least two distinct values.
+pub fn linear_fit(xs: &[f64], ys: &[f64]) -> (f64, f64) {
+    weighted_linear_fit(xs, ys, &vec![1.0; xs.len()])
+}
+
+/// Returns the slope and intercept of the line minimizing the squared residuals weighted by `ws`.
+pub fn weighted_linear_fit(xs: &[f64], ys: &[f64], ws: &[f64]) -> (f64, f64) {
+    let w = ws.iter().sum::<f64>();
+    let mean = |vs: &[f64]| vs.iter().zip(ws).map(|(v, w)| v * w).sum::<f64>() / w;
+    let (mean_x, mean_y) = (mean(xs), mean(ys));
+    let (cov, var) = (xs.iter().zip(ys).zip(ws)).fold((0.0, 0.0), |(cov, var), ((x, y), w)| {
+        (
+            cov + w * (x - mean_x) * (y - mean_y),
+            var + w * (x - mean_x).powi(2),
+        )
+    });
+    let slope = cov / var;
+    (slope, mean_y - slope * mean_x)
+}
+
+/// Returns the confidence interval of the median of `samples` with z-score `z`, using order
+/// statistics so no distribution is assumed. Returns `None` if there are too few samples for
+/// the interval to exclude the extremes.
+pub fn median_ci(samples: &[f64], z: f64) -> Option<(f64, f64)> {
+    let n = samples.len() as f64;
+    let half_width = z * n.sqrt() / 2.0;
+    let lo = (n / 2.0 - half_width).floor();
+    let hi = (n / 2.0 + half_width).ceil();
+    if lo < 1.0 || hi > n {
+        return None;
+    }
+    let sorted = sorted(samples);
+    Some((sorted[lo as usize - 1], sorted[hi as usize - 1]))
+}
+
+/// Returns the width of the confidence interval of the median relative to the median, or
+/// `f64::INFINITY` if there are too few samples.
+pub fn median_ci_relative_width(samples: &[f64], z: f64) -> f64 {
+    match median_ci(samples, z) {
+        Some((lo, hi)) => (hi - lo) / median(samples),
+        None => f64::INFINITY,
+    }
+}
+
+/// Returns the two-sided p-value of the Mann-Whitney U test of `a` and `b` being drawn from the
+/// same distribution, by the normal approximation with correction for ties and continuity, which
+/// holds for about 8 or more samples each.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> f64 {
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+    let mut pooled = (a.iter().map(|v| (*v, true)))
+        .chain(b.iter().map(|v| (*v, false)))
+        .collect::<Vec<_>>();
+    pooled.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Ranks from 1, with ties of `t` values given their mean rank.
+    let (mut start, mut rank_sum_a, mut ties) = (0, 0.0, 0.0);
+    for group in pooled.chunk_by(|x, y| x.0 == y.0) {
+        let t = group.len() as f64;
+        let rank = start as f64 + (t + 1.0) / 2.0;
+        rank_sum_a += rank * group.iter().filter(|(_, in_a)| *in_a).count() as f64;
+        ties += t.powi(3) - t;
+        start += group.len();
+    }
+
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mean = n_a * n_b / 2.0;
+    let variance = n_a * n_b / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance == 0.0 {
+        return 1.0;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    erfc(z / 2f64.sqrt())
+}
+
+/// Returns the complementary error function with relative error below `1.2e-7`, by the Chebyshev
+/// fit of Numerical Recipes `erfcc`.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| c + t * acc);
+    let r = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stats::{mann_whitney_u, median, median_ci, quantile, Z_95};
+
+    #[test]
+    fn median_ci_bounds() {
+        let samples = (1..=20).rev().map(f64::from).collect::<Vec<_>>();
+        assert_eq!(median(&samples), 10.5);
+        assert_eq!(median_ci(&samples, Z_95), Some((5.0, 15.0)));
+        assert_eq!(median_ci(&samples[..5], Z_95), None);
+        assert_eq!(quantile(&samples, 0.5), median(&samples));
+        assert_eq!(quantile(&samples, 0.0), 1.0);
+        assert_eq!(quantile(&samples, 1.0), 20.0);
+    }
+
+    #[test]
+    fn mann_whitney() {
+        let a = (1..=5).map(f64::from).collect::<Vec<_>>();
+        let b = (6..=10).map(f64::from).collect::<Vec<_>>();
+        assert!((mann_whitney_u(&a, &b) - 0.01219).abs() < 1e-4);
+        assert_eq!(mann_whitney_u(&a, &b), mann_whitney_u(&b, &a));
+        assert!((mann_whitney_u(&a, &a) - 1.0).abs() < 1e-6);
+        assert_eq!(mann_whitney_u(&[1.0; 4], &[1.0; 4]), 1.0);
+    }
+}
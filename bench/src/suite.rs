@@ -0,0 +1,391 @@
+//! Suite of benchmark runs across backend packages, each run as a supervised child process.
+//!
+//! A suite manifest is a TOML file like:
+//!
+//! ```toml
+//! output = "results.json"
+//! timeout = 1800
+//! threads = [24]
+//! log_inv_rates = [1]
+//! log_permutations = "10..=20"
+//! args = ["--sample-size", "10"]
+//!
+//! [[package]]
+//! name = "plonky3"
+//! hashes = ["blake3", "keccak", "poseidon2"]
+//! ```
+//!
+//! Every field but `package` can be overridden per package, and unknown fields are rejected. PCS
+//! parameters other than the rate, i.e. `security_bits`, `pow_bits` and `log_final_poly_lens`, are
+//! only passed to runs if given.
+
+use crate::{
+    report::Record,
+    util::{human_time, parse_range},
+};
+use serde::{de, Deserialize, Deserializer};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs::{self, File},
+    io,
+    path::{self, Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Path of the merged results, relative to the manifest.
+    #[serde(default = "default_output")]
+    pub output: PathBuf,
+    #[serde(flatten)]
+    pub defaults: Options,
+    pub package: Vec<Package>,
+}
+
+fn default_output() -> PathBuf {
+    "results.json".into()
+}
+
+/// Options of runs, which are set in the manifest top level and overridden by each package.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Options {
+    /// Timeout of each run in seconds.
+    pub timeout: Option<u64>,
+    pub threads: Option<Vec<usize>>,
+    pub log_inv_rates: Option<Vec<usize>>,
+    /// Target of conjectured security bits, from which the number of queries of each rate and
+    /// grinding is derived, see [`crate::util::PcsParams::num_queries`].
+    pub security_bits: Option<usize>,
+    pub pow_bits: Option<Vec<usize>>,
+    pub log_final_poly_lens: Option<Vec<usize>>,
+    #[serde(default, deserialize_with = "deserialize_range")]
+    pub log_permutations: Option<Vec<usize>>,
+    /// Extra arguments passed to each run, e.g. `["--sample-size", "10"]`.
+    pub args: Option<Vec<String>>,
+}
+
+impl Options {
+    fn or(&self, defaults: &Self) -> Self {
+        Self {
+            timeout: self.timeout.or(defaults.timeout),
+            threads: self.threads.clone().or_else(|| defaults.threads.clone()),
+            log_inv_rates: self
+                .log_inv_rates
+                .clone()
+                .or_else(|| defaults.log_inv_rates.clone()),
+            security_bits: self.security_bits.or(defaults.security_bits),
+            pow_bits: self.pow_bits.clone().or_else(|| defaults.pow_bits.clone()),
+            log_final_poly_lens: self
+                .log_final_poly_lens
+                .clone()
+                .or_else(|| defaults.log_final_poly_lens.clone()),
+            log_permutations: self
+                .log_permutations
+                .clone()
+                .or_else(|| defaults.log_permutations.clone()),
+            args: self.args.clone().or_else(|| defaults.args.clone()),
+        }
+    }
+}
+
+/// Accepts either a list `[10, 12]` or a range string in the format of [`parse_range`].
+fn deserialize_range<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<usize>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Range {
+        List(Vec<usize>),
+        Str(String),
+    }
+    match Range::deserialize(d)? {
+        Range::List(list) => Ok(Some(list)),
+        Range::Str(value) => parse_range(&value)
+            .map(Some)
+            .ok_or_else(|| de::Error::custom(format!("invalid range: {value}"))),
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Package {
+    /// Directory of the backend package, relative to the manifest.
+    pub name: String,
+    pub hashes: Vec<String>,
+    #[serde(flatten)]
+    pub options: Options,
+    /// Keys of no field, rejected by [`Manifest::load`] as `deny_unknown_fields` doesn't apply
+    /// with flattened fields.
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+/// A single run of a backend binary.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub package: String,
+    pub hash: String,
+    pub num_threads: usize,
+    pub log_inv_rate: usize,
+    pub security_bits: Option<usize>,
+    pub pow_bits: usize,
+    pub log_final_poly_len: Option<usize>,
+    pub log_permutations: usize,
+    pub timeout: Duration,
+    pub args: Vec<String>,
+}
+
+impl Job {
+    /// Returns the file stem of outputs, e.g. `t24_r1_keccak_lp20`, with PCS parameters other
+    /// than the rate appended if given, e.g. `t24_r2_keccak_lp20_s100_g16_f3`.
+    pub fn name(&self) -> String {
+        let mut name = format!(
+            "t{}_r{}_{}_lp{}",
+            self.num_threads, self.log_inv_rate, self.hash, self.log_permutations
+        );
+        if let Some(security_bits) = self.security_bits {
+            name += &format!("_s{security_bits}");
+        }
+        if self.pow_bits != 0 {
+            name += &format!("_g{}", self.pow_bits);
+        }
+        if let Some(log_final_poly_len) = self.log_final_poly_len {
+            name += &format!("_f{log_final_poly_len}");
+        }
+        name
+    }
+
+    fn envs(&self) -> Vec<(&'static str, String)> {
+        let mut envs = vec![
+            ("RAYON_NUM_THREADS", self.num_threads.to_string()),
+            ("PCS_LOG_INV_RATE", self.log_inv_rate.to_string()),
+            ("PCS_POW_BITS", self.pow_bits.to_string()),
+        ];
+        envs.extend(
+            self.security_bits
+                .map(|bits| ("PCS_SECURITY_BITS", bits.to_string())),
+        );
+        envs.extend(
+            self.log_final_poly_len
+                .map(|len| ("PCS_LOG_FINAL_POLY_LEN", len.to_string())),
+        );
+        envs
+    }
+}
+
+impl Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} threads={} rate=1/{} perm=2^{}",
+            self.package,
+            self.hash,
+            self.num_threads,
+            1 << self.log_inv_rate,
+            self.log_permutations
+        )?;
+        if let Some(security_bits) = self.security_bits {
+            write!(f, " security={security_bits}")?;
+        }
+        if self.pow_bits != 0 {
+            write!(f, " pow={}", self.pow_bits)?;
+        }
+        if let Some(log_final_poly_len) = self.log_final_poly_len {
+            write!(f, " final=2^{log_final_poly_len}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Done(Box<Record>),
+    Failed(ExitStatus),
+    TimedOut,
+    Error(io::Error),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Done(record) => write!(f, "{}", human_time(record.report.time)),
+            Self::Failed(status) => write!(f, "failed with {status}"),
+            Self::TimedOut => write!(f, "timed out"),
+            Self::Error(err) => write!(f, "error: {err}"),
+        }
+    }
+}
+
+impl Manifest {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let manifest = toml::from_str::<Self>(content).map_err(|err| err.to_string())?;
+        for package in &manifest.package {
+            if let Some(key) = package.unknown.keys().next() {
+                return Err(format!(
+                    "unknown field `{key}` of package `{}`",
+                    package.name
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Returns all runs of the suite, ordered by package, hash, threads, PCS parameters and size.
+    pub fn jobs(&self) -> Vec<Job> {
+        let mut jobs = Vec::new();
+        for package in &self.package {
+            let options = package.options.or(&self.defaults);
+            let pcs = product(
+                options.log_inv_rates.clone().unwrap_or_else(|| vec![1]),
+                options.pow_bits.clone().unwrap_or_else(|| vec![0]),
+                options
+                    .log_final_poly_lens
+                    .clone()
+                    .map_or_else(|| vec![None], |lens| lens.into_iter().map(Some).collect()),
+            );
+            for hash in &package.hashes {
+                for num_threads in options.threads.clone().unwrap_or_else(|| vec![24]) {
+                    for &(log_inv_rate, pow_bits, log_final_poly_len) in &pcs {
+                        for log_permutations in options
+                            .log_permutations
+                            .clone()
+                            .unwrap_or_else(|| (10..=20).collect())
+                        {
+                            jobs.push(Job {
+                                package: package.name.clone(),
+                                hash: hash.clone(),
+                                num_threads,
+                                log_inv_rate,
+                                security_bits: options.security_bits,
+                                pow_bits,
+                                log_final_poly_len,
+                                log_permutations,
+                                timeout: Duration::from_secs(options.timeout.unwrap_or(1800)),
+                                args: options.args.clone().unwrap_or_default(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        jobs
+    }
+}
+
+fn product<A: Copy, B: Copy, C: Copy>(a: Vec<A>, b: Vec<B>, c: Vec<C>) -> Vec<(A, B, C)> {
+    a.iter()
+        .flat_map(|a| b.iter().flat_map(|b| c.iter().map(|c| (*a, *b, *c))))
+        .collect()
+}
+
+/// Builds the binary of the package in `dir` and returns its path, so compilation doesn't count
+/// towards the timeouts, and a timed out run can be killed without leaving `cargo`'s child behind.
+pub fn build(dir: &Path) -> io::Result<PathBuf> {
+    let output = Command::new("cargo")
+        .args([
+            "build",
+            "--release",
+            "--message-format=json-render-diagnostics",
+        ])
+        .current_dir(dir)
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "failed to build {}: {}",
+            dir.display(),
+            output.status
+        )));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|message| Some(PathBuf::from(message.get("executable")?.as_str()?)))
+        .next_back()
+        .ok_or_else(|| io::Error::other(format!("no binary in {}", dir.display())))
+}
+
+/// Runs `job` with binary `exe` in `<root>/<package>`, writing its stdout and stderr to
+/// `report/<name>.log` and its record to `report/<name>.json`, and kills it once the timeout
+/// elapses.
+pub fn run(root: &Path, exe: &Path, job: &Job) -> Outcome {
+    let dir = match path::absolute(root.join(&job.package).join("report")) {
+        Ok(dir) => dir,
+        Err(err) => return Outcome::Error(err),
+    };
+    let spawn = || -> io::Result<Child> {
+        fs::create_dir_all(&dir)?;
+        let log = File::create(dir.join(format!("{}.log", job.name())))?;
+        Command::new(exe)
+            .args(["--hash", &job.hash])
+            .args(["--log-permutations", &job.log_permutations.to_string()])
+            .arg("--output")
+            .arg(dir.join(format!("{}.json", job.name())))
+            .args(&job.args)
+            .envs(job.envs())
+            .current_dir(root.join(&job.package))
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+    };
+    let mut child = match spawn() {
+        Ok(child) => child,
+        Err(err) => return Outcome::Error(err),
+    };
+
+    let deadline = Instant::now() + job.timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Outcome::TimedOut;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(err) => return Outcome::Error(err),
+        }
+    };
+    if !status.success() {
+        return Outcome::Failed(status);
+    }
+
+    match crate::report::load([dir.join(format!("{}.json", job.name()))]) {
+        Ok(mut records) if records.len() == 1 => Outcome::Done(Box::new(records.remove(0))),
+        Ok(_) => Outcome::Error(io::Error::other("expected a single record")),
+        Err(err) => Outcome::Error(err),
+    }
+}
+
+/// Estimates remaining time of `remaining` jobs by the mean time of `done` jobs, which is
+/// dominated by warm-up and sampling budgets rather than size.
+pub fn eta(done: &[Duration], remaining: usize) -> Option<Duration> {
+    let total = done.iter().sum::<Duration>();
+    (!done.is_empty()).then(|| total / done.len() as u32 * remaining as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::suite::Manifest;
+
+    #[test]
+    fn unknown_fields() {
+        let manifest = "threads = [1]\n[[package]]\nname = \"plonky3\"\nhashes = [\"keccak\"]\n";
+        let manifest = Manifest::parse(manifest).unwrap();
+        assert_eq!(manifest.package[0].options.threads, None);
+        assert_eq!(manifest.jobs()[0].num_threads, 1);
+
+        let typo =
+            "[[package]]\nname = \"plonky3\"\nhashes = [\"keccak\"]\ntimeout = 1\nthread = [1]\n";
+        let err = Manifest::parse(typo).unwrap_err();
+        assert_eq!(err, "unknown field `thread` of package `plonky3`");
+        assert!(Manifest::parse("thread = [1]\npackage = []\n").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Background sampling of heap and RSS over time, with marks of phase boundaries.
+//!
+//! Phases are marked by [`enter`], which is a no-op unless a [`Sampler`] is running.
+
+use crate::alloc;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Start and marks of the running [`Sampler`], if any.
+static MARKS: Mutex<Option<(Instant, Vec<Mark>)>> = Mutex::new(None);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sample {
+    /// Time since the sampler started.
+    pub time: Duration,
+    /// Bytes allocated, if [`alloc::TrackingAllocator`] is installed.
+    pub heap: Option<usize>,
+    /// Resident set size in bytes, only available on Linux.
+    pub rss: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mark {
+    /// Time since the sampler started.
+    pub time: Duration,
+    pub name: String,
+    /// Whether the phase is entered or exited.
+    pub enter: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    pub interval: Duration,
+    pub samples: Vec<Sample>,
+    pub marks: Vec<Mark>,
+}
+
+/// Peak heap and RSS sampled while a phase is the innermost one entered.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhasePeak {
+    /// Nested phase names joined by `/`, or empty outside of any phase.
+    pub phase: String,
+    pub heap: Option<usize>,
+    pub rss: Option<usize>,
+}
+
+impl Timeline {
+    /// Returns the peaks of each phase in order of first sample.
+    pub fn phase_peaks(&self) -> Vec<PhasePeak> {
+        let mut peaks = Vec::<PhasePeak>::new();
+        let mut stack = Vec::new();
+        let mut marks = self.marks.iter().peekable();
+        for sample in &self.samples {
+            while let Some(mark) = marks.next_if(|mark| mark.time <= sample.time) {
+                if mark.enter {
+                    stack.push(mark.name.as_str());
+                } else if let Some(idx) = stack.iter().rposition(|name| *name == mark.name) {
+                    stack.truncate(idx);
+                }
+            }
+            let phase = stack.join("/");
+            let idx = match peaks.iter().position(|peak| peak.phase == phase) {
+                Some(idx) => idx,
+                None => {
+                    peaks.push(PhasePeak {
+                        phase,
+                        heap: None,
+                        rss: None,
+                    });
+                    peaks.len() - 1
+                }
+            };
+            let peak = &mut peaks[idx];
+            peak.heap = peak.heap.max(sample.heap);
+            peak.rss = peak.rss.max(sample.rss);
+        }
+        peaks
+    }
+}
+
+/// Background thread sampling heap and RSS every `interval` until [`Sampler::stop`].
+pub struct Sampler {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<Sample>>,
+    interval: Duration,
+}
+
+impl Sampler {
+    /// Starts sampling, panics if another sampler is running.
+    pub fn start(interval: Duration) -> Self {
+        let start = Instant::now();
+        let previous = MARKS.lock().unwrap().replace((start, Vec::new()));
+        assert!(previous.is_none(), "a timeline sampler is already running");
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let mut samples = Vec::new();
+                loop {
+                    samples.push(Sample {
+                        time: start.elapsed(),
+                        heap: alloc::is_tracking().then(alloc::current),
+                        rss: rss(),
+                    });
+                    if stop.load(Relaxed) {
+                        return samples;
+                    }
+                    thread::sleep(interval);
+                }
+            }
+        });
+        Self {
+            stop,
+            handle,
+            interval,
+        }
+    }
+
+    /// Stops sampling, taking a last sample, and returns the timeline.
+    pub fn stop(self) -> Timeline {
+        self.stop.store(true, Relaxed);
+        let samples = self.handle.join().unwrap();
+        let (_, marks) = MARKS.lock().unwrap().take().unwrap();
+        Timeline {
+            interval: self.interval,
+            samples,
+            marks,
+        }
+    }
+}
+
+/// Guard of a phase entered by [`enter`], which marks its exit on drop.
+pub struct Phase(Option<&'static str>);
+
+impl Drop for Phase {
+    fn drop(&mut self) {
+        if let Some(name) = self.0 {
+            mark(name, false);
+        }
+    }
+}
+
+/// Marks entering phase `name` if a [`Sampler`] is running, and its exit once the returned guard
+/// is dropped.
+pub fn enter(name: &'static str) -> Phase {
+    Phase(mark(name, true).then_some(name))
+}
+
+/// Records a mark of phase `name`, returning whether a [`Sampler`] is running.
+pub fn mark(name: &str, enter: bool) -> bool {
+    let mut marks = MARKS.lock().unwrap();
+    let Some((start, marks)) = marks.as_mut() else {
+        return false;
+    };
+    marks.push(Mark {
+        time: start.elapsed(),
+        name: name.to_string(),
+        enter,
+    });
+    true
+}
+
+/// Returns the current resident set size from `/proc/self/statm`, only available on Linux.
+pub fn rss() -> Option<usize> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let pages = statm.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    Some(pages * page_size())
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
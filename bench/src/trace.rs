@@ -0,0 +1,220 @@
+//! Tracing subscriber shared by all backends, collecting spans into a Chrome trace-event file
+//! (viewable in `chrome://tracing` or Perfetto) and a folded-stack file (for `inferno` or
+//! `flamegraph.pl`).
+//!
+//! Spans are filtered by env `RUST_LOG` in the format of [`Targets`], defaulting to `info`.
+
+use crate::timeline;
+use serde::Serialize;
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    layer::Context,
+    prelude::*,
+    registry::LookupSpan,
+    Layer, Registry,
+};
+
+/// Maximum depth of spans marked as phases of [`timeline`], since deeper spans are usually
+/// entered concurrently on worker threads.
+const TIMELINE_DEPTH: usize = 2;
+
+#[derive(Serialize)]
+struct Event {
+    name: &'static str,
+    cat: &'static str,
+    ph: &'static str,
+    /// Start in microseconds.
+    ts: f64,
+    /// Duration in microseconds.
+    dur: f64,
+    pid: u32,
+    tid: u64,
+}
+
+#[derive(Default)]
+struct Collected {
+    events: Vec<Event>,
+    /// Self time of each stack of span names joined by `;`.
+    folded: BTreeMap<String, Duration>,
+}
+
+/// Timing of a span, stored in its extensions.
+#[derive(Default)]
+struct Timing {
+    entered: Vec<Instant>,
+    busy: Duration,
+    children: Duration,
+}
+
+/// [`Layer`] recording entered spans.
+#[derive(Clone)]
+pub struct ProfileLayer {
+    start: Instant,
+    collected: Arc<Mutex<Collected>>,
+}
+
+thread_local! {
+    static THREAD_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    THREAD_ID.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT.fetch_add(1, Relaxed));
+        }
+        id.get()
+    })
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ProfileLayer {
+    fn on_new_span(&self, _: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        span.extensions_mut().insert(Timing::default());
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        if span.scope().count() <= TIMELINE_DEPTH {
+            timeline::mark(span.name(), true);
+        }
+        if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+            timing.entered.push(Instant::now());
+        };
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let end = Instant::now();
+        let span = ctx.span(id).unwrap();
+        let Some(entered) = span
+            .extensions_mut()
+            .get_mut::<Timing>()
+            .and_then(|timing| timing.entered.pop())
+        else {
+            return;
+        };
+        let elapsed = end - entered;
+        if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+            timing.busy += elapsed;
+        }
+        if let Some(parent) = span.parent() {
+            if let Some(timing) = parent.extensions_mut().get_mut::<Timing>() {
+                timing.children += elapsed;
+            }
+        }
+        if span.scope().count() <= TIMELINE_DEPTH {
+            timeline::mark(span.name(), false);
+        }
+
+        let metadata = span.metadata();
+        self.collected.lock().unwrap().events.push(Event {
+            name: metadata.name(),
+            cat: metadata.target(),
+            ph: "X",
+            ts: (entered - self.start).as_secs_f64() * 1e6,
+            dur: elapsed.as_secs_f64() * 1e6,
+            pid: std::process::id(),
+            tid: thread_id(),
+        });
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let Some(self_time) = span
+            .extensions()
+            .get::<Timing>()
+            .map(|timing| timing.busy.saturating_sub(timing.children))
+        else {
+            return;
+        };
+        let stack = span
+            .scope()
+            .from_root()
+            .map(|span| span.name())
+            .collect::<Vec<_>>()
+            .join(";");
+        *self
+            .collected
+            .lock()
+            .unwrap()
+            .folded
+            .entry(stack)
+            .or_default() += self_time;
+    }
+}
+
+/// Guard of [`init`], which writes the collected spans on drop.
+pub struct TraceGuard {
+    layer: ProfileLayer,
+    prefix: PathBuf,
+}
+
+impl TraceGuard {
+    /// Writes Chrome trace events to `<prefix>.json` and folded stacks to `<prefix>.folded`,
+    /// with self time in microseconds.
+    pub fn write(&self) -> io::Result<()> {
+        let collected = self.layer.collected.lock().unwrap();
+        let trace = serde_json::json!({
+            "traceEvents": collected.events,
+            "displayTimeUnit": "ms",
+        });
+        fs::write(with_extension(&self.prefix, "json"), trace.to_string())?;
+        let folded = collected
+            .folded
+            .iter()
+            .map(|(stack, time)| format!("{stack} {}\n", time.as_micros()))
+            .collect::<String>();
+        fs::write(with_extension(&self.prefix, "folded"), folded)
+    }
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        match self.write() {
+            Ok(()) => eprintln!(
+                "trace written to {} and {}",
+                with_extension(&self.prefix, "json").display(),
+                with_extension(&self.prefix, "folded").display()
+            ),
+            Err(err) => eprintln!("failed to write trace: {err}"),
+        }
+    }
+}
+
+fn with_extension(prefix: &Path, extension: &str) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+/// Installs the global subscriber collecting spans until the returned guard is dropped.
+pub fn init(prefix: impl Into<PathBuf>) -> TraceGuard {
+    let filter = env::var("RUST_LOG")
+        .ok()
+        .and_then(|filter| filter.parse::<Targets>().ok())
+        .unwrap_or_else(|| Targets::new().with_default(LevelFilter::INFO));
+    let layer = ProfileLayer {
+        start: Instant::now(),
+        collected: Default::default(),
+    };
+    Registry::default()
+        .with(layer.clone().with_filter(filter))
+        .init();
+    TraceGuard {
+        layer,
+        prefix: prefix.into(),
+    }
+}
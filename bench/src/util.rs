@@ -1,16 +1,172 @@
-use std::{env, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, str::FromStr, time::Duration};
 
+/// Returns the log of the inverse rate from env `PCS_LOG_INV_RATE`, 1 by default, rejecting 0 as
+/// no redundancy.
 pub fn pcs_log_inv_rate() -> usize {
-    env::var("PCS_LOG_INV_RATE")
-        .ok()
-        .and_then(|r| r.parse().ok())
-        .unwrap_or(1)
+    let log_inv_rate = env_var("PCS_LOG_INV_RATE").unwrap_or(1);
+    assert!(log_inv_rate > 0, "invalid PCS_LOG_INV_RATE: 0");
+    log_inv_rate
+}
+
+/// Returns the length of chains of [`Workload::Chains`](crate::workload::Workload::Chains) from
+/// env `CHAIN_LENGTH`, 16 by default.
+pub fn chain_length() -> usize {
+    env_var("CHAIN_LENGTH").unwrap_or(16)
+}
+
+fn env_var<T: FromStr>(key: &str) -> Option<T> {
+    env::var(key).ok().and_then(|value| value.parse().ok())
+}
+
+/// Parameters of the PCS, which backends apply where supported and otherwise keep their own
+/// defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PcsParams {
+    pub log_inv_rate: usize,
+    /// Target of conjectured security bits, from queries and grinding.
+    pub security_bits: Option<usize>,
+    /// Bits of proof of work before sampling queries.
+    pub pow_bits: usize,
+    /// Number of queries, overriding the one derived from `security_bits`.
+    pub num_queries: Option<usize>,
+    /// Log of length of the final polynomial of FRI.
+    pub log_final_poly_len: Option<usize>,
+}
+
+impl PcsParams {
+    /// Returns the number of queries if given, otherwise the fewest reaching `security_bits`
+    /// (or `default_security_bits` if not given) with grinding of `pow_bits`.
+    pub fn num_queries(&self, default_security_bits: usize) -> usize {
+        self.num_queries.unwrap_or_else(|| {
+            let security_bits = self.security_bits.unwrap_or(default_security_bits);
+            security_bits
+                .saturating_sub(self.pow_bits)
+                .div_ceil(self.log_inv_rate)
+        })
+    }
+}
+
+/// Returns [`PcsParams`] from env `PCS_LOG_INV_RATE`, `PCS_SECURITY_BITS`, `PCS_POW_BITS`,
+/// `PCS_NUM_QUERIES` and `PCS_LOG_FINAL_POLY_LEN`.
+pub fn pcs_params() -> PcsParams {
+    PcsParams {
+        log_inv_rate: pcs_log_inv_rate(),
+        security_bits: env_var("PCS_SECURITY_BITS"),
+        pow_bits: env_var("PCS_POW_BITS").unwrap_or(0),
+        num_queries: env_var("PCS_NUM_QUERIES"),
+        log_final_poly_len: env_var("PCS_LOG_FINAL_POLY_LEN"),
+    }
+}
+
+/// Returns `name` in kebab case, as clap names variants of value enums, e.g. `m31-poseidon` of
+/// `M31Poseidon`.
+pub fn kebab_case(name: &str) -> String {
+    let mut kebab = String::new();
+    let mut prev = None::<char>;
+    for c in name.chars() {
+        if c.is_uppercase() && prev.is_some_and(|prev| prev.is_lowercase() || prev.is_numeric()) {
+            kebab.push('-');
+        }
+        kebab.extend(c.to_lowercase());
+        prev = Some(c);
+    }
+    kebab
+}
+
+/// Selects one of `configs`, pairs of hash and config names with the default config of each hash
+/// first, by `hash` given as `<hash>` or `<hash>@<config>`, and by `config`. Returns its index and
+/// its label in records, which omits the config if it's the default.
+pub fn select_config(
+    configs: &[(String, &str)],
+    hash: &str,
+    config: Option<&str>,
+) -> Result<(usize, String), String> {
+    let (hash, config) = match (hash.split_once('@'), config) {
+        (Some((_, suffix)), Some(config)) if suffix != config => {
+            return Err(format!("`{hash}` conflicts with `--config {config}`"));
+        }
+        (Some((hash, suffix)), _) => (hash, Some(suffix)),
+        (None, config) => (hash, config),
+    };
+    let default = configs
+        .iter()
+        .position(|(name, _)| name == hash)
+        .ok_or_else(|| format!("unknown hash `{hash}`, see `--list`"))?;
+    let Some(config) = config else {
+        return Ok((default, hash.to_string()));
+    };
+    let index = configs
+        .iter()
+        .position(|(name, other)| name == hash && *other == config)
+        .ok_or_else(|| format!("unknown config `{config}` of `{hash}`, see `--list`"))?;
+    let label = if index == default {
+        hash.to_string()
+    } else {
+        format!("{hash}@{config}")
+    };
+    Ok((index, label))
+}
+
+/// Returns the `log_permutations` to benchmark, parsed from env `LOG_PERMUTATIONS` as a range
+/// `10..13`, an inclusive range `10..=12` or a list `10,12,14`, otherwise `default`.
+pub fn log_permutations(default: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let Ok(value) = env::var("LOG_PERMUTATIONS") else {
+        return default.into_iter().collect();
+    };
+    parse_range(&value).unwrap_or_else(|| panic!("invalid LOG_PERMUTATIONS: {value}"))
+}
+
+pub fn parse_range(value: &str) -> Option<Vec<usize>> {
+    let value = value.trim();
+    if let Some((start, end)) = value.split_once("..=") {
+        Some((start.trim().parse().ok()?..=end.trim().parse().ok()?).collect())
+    } else if let Some((start, end)) = value.split_once("..") {
+        Some((start.trim().parse().ok()?..end.trim().parse().ok()?).collect())
+    } else {
+        value.split(',').map(|v| v.trim().parse().ok()).collect()
+    }
 }
 
 pub fn po2(exps: impl IntoIterator<Item = usize>) -> impl Iterator<Item = usize> {
     exps.into_iter().map(|exp| 1 << exp)
 }
 
+/// Returns the peak resident set size of the current process in bytes, from `/proc` on Linux and
+/// `getrusage` on other Unix systems.
+pub fn peak_rss() -> Option<usize> {
+    let Ok(status) = fs::read_to_string("/proc/self/status") else {
+        return max_rss();
+    };
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    Some(kb << 10)
+}
+
+/// Returns `ru_maxrss` of the current process in bytes, which is in kilobytes except on macOS.
+#[cfg(unix)]
+fn max_rss() -> Option<usize> {
+    // SAFETY: `usage` is a valid `rusage` to be filled.
+    let usage = unsafe {
+        let mut usage = core::mem::zeroed::<libc::rusage>();
+        if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+            return None;
+        }
+        usage
+    };
+    let max_rss = usize::try_from(usage.ru_maxrss).ok()?;
+    Some(if cfg!(target_os = "macos") {
+        max_rss
+    } else {
+        max_rss << 10
+    })
+}
+
+#[cfg(not(unix))]
+fn max_rss() -> Option<usize> {
+    None
+}
+
 pub fn human_time(time: Duration) -> String {
     let time = time.as_nanos();
     if time < 1_000 {
@@ -43,3 +199,42 @@ pub fn human_throughput(throughput: f64) -> String {
         format!("{:.2} M/s", throughput / 1_000_000.0)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::util::{kebab_case, select_config};
+
+    /// The fallback of [`super::peak_rss`], which can't be compared to `/proc` as Linux keeps the
+    /// peak of the process before `exec` in `ru_maxrss`.
+    #[cfg(unix)]
+    #[test]
+    fn max_rss() {
+        assert!(super::max_rss().is_some_and(|max_rss| max_rss > 0));
+    }
+
+    #[test]
+    fn select_configs() {
+        assert_eq!(kebab_case("M31Poseidon"), "m31-poseidon");
+        assert_eq!(kebab_case("Blake2s"), "blake2s");
+
+        let configs = [
+            ("keccak".to_string(), "baby_bear"),
+            ("keccak".to_string(), "koala_bear"),
+            ("poseidon2".to_string(), "koala_bear"),
+        ];
+        let select = |hash, config| select_config(&configs, hash, config);
+        assert_eq!(select("keccak", None), Ok((0, "keccak".to_string())));
+        assert_eq!(
+            select("keccak@baby_bear", None),
+            Ok((0, "keccak".to_string()))
+        );
+        assert_eq!(
+            select("keccak", Some("koala_bear")),
+            Ok((1, "keccak@koala_bear".to_string()))
+        );
+        assert_eq!(select("poseidon2@koala_bear", None).unwrap().0, 2);
+        assert!(select("keccak@baby_bear", Some("koala_bear")).is_err());
+        assert!(select("poseidon2", Some("baby_bear")).is_err());
+        assert!(select("blake3", None).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Computations proven by backends with their permutations.
+
+use crate::hash::HashDescriptor;
+use core::fmt::{self, Display};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Workload {
+    /// Independent permutations of random states.
+    #[default]
+    Permutations,
+    /// Root of a binary Merkle tree over `2^k` leaves, with `2^k - 1` compressions of a single
+    /// permutation each, whose outputs are constrained to be inputs of the next level and the root
+    /// is a public output.
+    MerkleTree,
+    /// Chains of `length` permutations, e.g. as in verification of WOTS signatures, with the
+    /// digest of each permutation constrained to be the input of the next one.
+    Chains { length: usize },
+}
+
+impl Workload {
+    /// Returns the number of 2-to-1 compressions proven by `num_permutations` permutations of `H`.
+    ///
+    /// Compressions of [`Self::MerkleTree`] and steps of [`Self::Chains`] take a single
+    /// permutation with the digest of the backend, rather than
+    /// [`HashDescriptor::PERMUTATIONS_PER_COMPRESSION`].
+    pub fn compressions<H: HashDescriptor>(self, num_permutations: usize) -> f64 {
+        match self {
+            Self::Permutations => num_permutations as f64 / H::PERMUTATIONS_PER_COMPRESSION as f64,
+            Self::MerkleTree | Self::Chains { .. } => num_permutations as f64,
+        }
+    }
+}
+
+impl Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Permutations => write!(f, "permutations"),
+            Self::MerkleTree => write!(f, "Merkle tree"),
+            Self::Chains { length } => write!(f, "chains of {length}"),
+        }
+    }
+}
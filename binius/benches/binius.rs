@@ -1,19 +1,44 @@
-use bench::{criterion::bench, util::po2};
+use bench::{
+    alloc::TrackingAllocator,
+    criterion::{bench, bench_verify, Metric, PeakHeap, ProofSize},
+    util::{log_permutations, po2},
+};
 use bench_binius::hash::{BiniusGroestl, BiniusKeccak};
 use criterion::{criterion_group, criterion_main, Criterion};
 
-fn bench_groestl(c: &mut Criterion) {
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+fn bench_groestl<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("bench_groestl");
 
     type H = BiniusGroestl;
-    bench::<H>(&mut group, "canonical_tower_groestl_mt", po2(10..13));
+    bench::<H, _>(
+        &mut group,
+        "canonical_tower_groestl_mt",
+        po2(log_permutations(10..13)),
+    );
+    bench_verify::<H, _>(
+        &mut group,
+        "canonical_tower_groestl_mt",
+        po2(log_permutations(10..13)),
+    );
 }
 
-fn bench_keccak(c: &mut Criterion) {
+fn bench_keccak<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("keccak");
 
     type H = BiniusKeccak;
-    bench::<H>(&mut group, "canonical_tower_groestl_mt", po2(10..13));
+    bench::<H, _>(
+        &mut group,
+        "canonical_tower_groestl_mt",
+        po2(log_permutations(10..13)),
+    );
+    bench_verify::<H, _>(
+        &mut group,
+        "canonical_tower_groestl_mt",
+        po2(log_permutations(10..13)),
+    );
 }
 
 criterion_group!(
@@ -21,4 +46,14 @@ criterion_group!(
     config = Criterion::default().sample_size(10);
     targets = bench_groestl, bench_keccak,
 );
-criterion_main!(benches);
+criterion_group!(
+    name = proof_size;
+    config = Criterion::default().with_measurement(ProofSize).sample_size(10).without_plots();
+    targets = bench_groestl, bench_keccak,
+);
+criterion_group!(
+    name = peak_heap;
+    config = Criterion::default().with_measurement(PeakHeap).sample_size(10).without_plots();
+    targets = bench_groestl, bench_keccak,
+);
+criterion_main!(benches, proof_size, peak_heap);
@@ -1,5 +1,52 @@
+use bench::{
+    cost::{self, CountedDigest},
+    hash::{GroestlP, HashDescriptor},
+    report::Description,
+};
+use binius_hash::{
+    groestl::{Groestl256, Groestl256ByteCompression},
+    PseudoCompressionFunction,
+};
+
 mod groestl;
 mod keccak;
+mod keccak_chain;
 
 pub use groestl::BiniusGroestl;
 pub use keccak::BiniusKeccak;
+pub use keccak_chain::BiniusKeccakChain;
+
+/// Returns [`Description`] of M3 proofs over the canonical tower with Groestl256 Merkle trees and
+/// challenger.
+fn describe(log_inv_rate: usize, security_bits: usize) -> Description {
+    Description {
+        field: "GF(2) canonical tower".to_string(),
+        extension_degree: 128,
+        arithmetization: "M3".to_string(),
+        pcs: "FRI-Binius".to_string(),
+        merkle_hash: Some("Groestl-256".to_string()),
+        transcript_hash: "Groestl-256".to_string(),
+        log_inv_rate: Some(log_inv_rate),
+        num_queries: None,
+        security_bits: Some(security_bits),
+        pow_bits: None,
+    }
+}
+
+/// [`Groestl256`] counting hash calls of verifiers for [`bench::cost`].
+pub type CountedGroestl256 = CountedDigest<Groestl256, GroestlP>;
+
+/// [`Groestl256ByteCompression`] counting compression calls of verifiers for [`bench::cost`].
+#[derive(Clone, Debug, Default)]
+pub struct CountedGroestl256ByteCompression(Groestl256ByteCompression);
+
+impl<T> PseudoCompressionFunction<T, 2> for CountedGroestl256ByteCompression
+where
+    Groestl256ByteCompression: PseudoCompressionFunction<T, 2>,
+{
+    fn compress(&self, input: [T; 2]) -> T {
+        cost::count_hash(size_of::<[T; 2]>());
+        cost::count_permutations(GroestlP::PERMUTATIONS_PER_COMPRESSION);
+        self.0.compress(input)
+    }
+}
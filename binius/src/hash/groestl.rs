@@ -1,7 +1,8 @@
 // Copied and modified from https://github.com/IrreducibleOSS/binius/blob/main/examples/groestl.rs.
 
+use crate::hash::{describe, CountedGroestl256, CountedGroestl256ByteCompression};
 use anyhow::{Error, Result};
-use bench::{util::pcs_log_inv_rate, HashInSnark};
+use bench::{codec::Codec, hash::GroestlP, report::Description, util::pcs_params, HashInSnark};
 use binius_compute::{cpu::alloc::CpuComputeAllocator, ComputeHolder};
 use binius_core::{
     constraint_system::{self, Proof},
@@ -70,7 +71,8 @@ pub struct BiniusGroestl {
 }
 
 impl HashInSnark for BiniusGroestl {
-    type Input = ();
+    type Hash = GroestlP;
+    type Input = Vec<[B8; 64]>;
     type Proof = Proof;
     type Error = Error;
 
@@ -79,10 +81,11 @@ impl HashInSnark for BiniusGroestl {
         Self: Sized,
     {
         let num_permutations = num_permutations.next_power_of_two();
+        let pcs = pcs_params();
         Self {
             num_permutations,
-            log_inv_rate: pcs_log_inv_rate(),
-            security_bits: 100,
+            log_inv_rate: pcs.log_inv_rate,
+            security_bits: pcs.security_bits.unwrap_or(100),
         }
     }
 
@@ -90,9 +93,13 @@ impl HashInSnark for BiniusGroestl {
         self.num_permutations
     }
 
-    fn generate_input(&self, _: impl RngCore) -> Self::Input {}
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+        repeat_with(|| array::from_fn(|_| B8::random(&mut rng)))
+            .take(self.num_permutations)
+            .collect()
+    }
 
-    fn prove(&self, _: Self::Input) -> Self::Proof {
+    fn prove(&self, events: Self::Input) -> Self::Proof {
         let mut allocator = CpuComputeAllocator::new(
             1 << (8 + log2_ceil_usize(self.num_permutations)
                 - PackedType::<OptimalUnderlier, B128>::LOG_WIDTH),
@@ -104,11 +111,6 @@ impl HashInSnark for BiniusGroestl {
         let boundaries = vec![];
         let table_sizes = vec![self.num_permutations];
 
-        let mut rng = rand::rng();
-        let events = repeat_with(|| array::from_fn::<_, 64, _>(|_| B8::random(&mut rng)))
-            .take(self.num_permutations)
-            .collect::<Vec<_>>();
-
         let mut witness = WitnessIndex::<PackedType<OptimalUnderlier, B128>>::new(&cs, &allocator);
         witness.fill_table_parallel(&table, &events).unwrap();
 
@@ -157,9 +159,9 @@ impl HashInSnark for BiniusGroestl {
         binius_core::constraint_system::verify::<
             OptimalUnderlier,
             CanonicalTowerFamily,
-            Groestl256,
-            Groestl256ByteCompression,
-            HasherChallenger<Groestl256>,
+            CountedGroestl256,
+            CountedGroestl256ByteCompression,
+            HasherChallenger<CountedGroestl256>,
         >(
             &ccs,
             self.log_inv_rate,
@@ -172,12 +174,20 @@ impl HashInSnark for BiniusGroestl {
         Ok(())
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(&proof.transcript).unwrap()
+    fn describe(&self) -> Description {
+        describe(self.log_inv_rate, self.security_bits)
+    }
+
+    fn field_bits() -> u32 {
+        128
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(&proof.transcript)
     }
 
-    fn deserialize_proof(data: &[u8]) -> Self::Proof {
-        let transcript = bincode::deserialize(data).unwrap();
+    fn decode_proof(data: &[u8], codec: Codec) -> Self::Proof {
+        let transcript = codec.decode(data);
         Proof { transcript }
     }
 }
@@ -1,7 +1,8 @@
 // Copied and modified from https://github.com/IrreducibleOSS/binius/blob/main/examples/keccak.rs.
 
+use crate::hash::{describe, CountedGroestl256, CountedGroestl256ByteCompression};
 use anyhow::{Error, Result};
-use bench::{util::pcs_log_inv_rate, HashInSnark};
+use bench::{codec::Codec, hash::KeccakF1600, report::Description, util::pcs_params, HashInSnark};
 use binius_compute::{cpu::alloc::CpuComputeAllocator, ComputeHolder};
 use binius_core::{
     constraint_system::{self, Proof},
@@ -72,7 +73,8 @@ pub struct BiniusKeccak {
 }
 
 impl HashInSnark for BiniusKeccak {
-    type Input = ();
+    type Hash = KeccakF1600;
+    type Input = Vec<StateMatrix<u64>>;
     type Proof = Proof;
     type Error = Error;
 
@@ -81,10 +83,11 @@ impl HashInSnark for BiniusKeccak {
         Self: Sized,
     {
         let num_permutations = num_permutations.next_power_of_two();
+        let pcs = pcs_params();
         Self {
             num_permutations,
-            log_inv_rate: pcs_log_inv_rate(),
-            security_bits: 100,
+            log_inv_rate: pcs.log_inv_rate,
+            security_bits: pcs.security_bits.unwrap_or(100),
         }
     }
 
@@ -92,9 +95,13 @@ impl HashInSnark for BiniusKeccak {
         self.num_permutations
     }
 
-    fn generate_input(&self, _: impl RngCore) -> Self::Input {}
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+        repeat_with(|| StateMatrix::from_fn(|_| rng.next_u64()))
+            .take(self.num_permutations)
+            .collect()
+    }
 
-    fn prove(&self, _: Self::Input) -> Self::Proof {
+    fn prove(&self, events: Self::Input) -> Self::Proof {
         let mut allocator = CpuComputeAllocator::new(
             1 << (11 + log2_ceil_usize(self.num_permutations)
                 - PackedType::<OptimalUnderlier, B128>::LOG_WIDTH),
@@ -106,11 +113,6 @@ impl HashInSnark for BiniusKeccak {
         let boundaries = vec![];
         let table_sizes = vec![self.num_permutations];
 
-        let mut rng = rand::rng();
-        let events = repeat_with(|| StateMatrix::from_fn(|_| rng.next_u64()))
-            .take(self.num_permutations)
-            .collect::<Vec<_>>();
-
         let mut witness = WitnessIndex::<PackedType<OptimalUnderlier, B128>>::new(&cs, &allocator);
         witness.fill_table_parallel(&table, &events).unwrap();
 
@@ -159,9 +161,9 @@ impl HashInSnark for BiniusKeccak {
         binius_core::constraint_system::verify::<
             OptimalUnderlier,
             CanonicalTowerFamily,
-            Groestl256,
-            Groestl256ByteCompression,
-            HasherChallenger<Groestl256>,
+            CountedGroestl256,
+            CountedGroestl256ByteCompression,
+            HasherChallenger<CountedGroestl256>,
         >(
             &ccs,
             self.log_inv_rate,
@@ -174,12 +176,20 @@ impl HashInSnark for BiniusKeccak {
         Ok(())
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(&proof.transcript).unwrap()
+    fn describe(&self) -> Description {
+        describe(self.log_inv_rate, self.security_bits)
+    }
+
+    fn field_bits() -> u32 {
+        128
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(&proof.transcript)
     }
 
-    fn deserialize_proof(data: &[u8]) -> Self::Proof {
-        let transcript = bincode::deserialize(data).unwrap();
+    fn decode_proof(data: &[u8], codec: Codec) -> Self::Proof {
+        let transcript = codec.decode(data);
         Proof { transcript }
     }
 }
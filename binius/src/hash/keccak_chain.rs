@@ -0,0 +1,381 @@
+use crate::hash::{describe, CountedGroestl256, CountedGroestl256ByteCompression};
+use anyhow::{Error, Result};
+use bench::{
+    codec::Codec,
+    hash::KeccakF1600,
+    report::Description,
+    util::{chain_length, pcs_params},
+    workload::Workload,
+    HashInSnark,
+};
+use binius_compute::{cpu::alloc::CpuComputeAllocator, ComputeHolder};
+use binius_core::{
+    constraint_system::{self, channel::ChannelId, Proof},
+    fiat_shamir::HasherChallenger,
+};
+use binius_fast_compute::layer::FastCpuLayerHolder;
+use binius_field::{
+    arch::OptimalUnderlier, as_packed_field::PackedType,
+    linear_transformation::PackedTransformationFactory, tower::CanonicalTowerFamily, BinaryField,
+    Field, PackedExtension, PackedFieldIndexable, PackedSubfield,
+};
+use binius_hal::make_portable_backend;
+use binius_hash::groestl::{Groestl256, Groestl256ByteCompression, Groestl256Parallel};
+use binius_m3::{
+    builder::{
+        Col, ConstraintSystem, TableFiller, TableId, TableWitnessSegment, WitnessIndex, B1, B128,
+        B64, B8,
+    },
+    gadgets::hash::keccak::{stacked::Keccakf, StateMatrix},
+};
+use binius_utils::checked_arithmetics::log2_ceil_usize;
+use core::iter::{once, repeat_with};
+use rand::RngCore;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of lanes, indexed by `x + 5 * y`.
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Keccak-f[1600] of `state` with lanes indexed by `x + 5 * y`, to compute the chains outside of
+/// the table.
+fn keccakf(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        let c: [u64; 5] = core::array::from_fn(|x| (0..5).fold(0, |c, y| c ^ state[x + 5 * y]));
+        for (idx, lane) in state.iter_mut().enumerate() {
+            let x = idx % 5;
+            *lane ^= c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        }
+        let mut b = [0; 25];
+        for (idx, &lane) in state.iter().enumerate() {
+            let (x, y) = (idx % 5, idx / 5);
+            b[y + 5 * ((2 * x + 3 * y) % 5)] = lane.rotate_left(RHO[idx]);
+        }
+        for (idx, lane) in state.iter_mut().enumerate() {
+            let (x, y) = (idx % 5, idx / 5);
+            *lane = b[idx] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+        }
+        state[0] ^= round_constant;
+    }
+}
+
+/// Step of a permutation in its chain, as a power of the multiplicative generator, so the steps of
+/// a chain are distinct and the boundaries fix its length.
+fn step(idx: usize) -> B64 {
+    (0..idx).fold(B64::ONE, |step, _| step * B64::MULTIPLICATIVE_GENERATOR)
+}
+
+fn lanes(state: &StateMatrix<Col<B64>>) -> impl Iterator<Item = Col<B64>> + '_ {
+    (0..5).flat_map(move |y| (0..5).map(move |x| state[(x, y)]))
+}
+
+/// Table of Keccak-f permutations of chains, each pulling its step and input state from a channel
+/// and pushing the next step and its output state, so the output of a permutation is the input of
+/// the next one of its chain. The chains start and end in [`EndsTable`].
+pub struct ChainTable {
+    table_id: TableId,
+    keccakf: Keccakf,
+    step_in: Col<B64>,
+    step_out: Col<B64>,
+}
+
+impl ChainTable {
+    pub fn new(cs: &mut ConstraintSystem, channel: ChannelId) -> Self {
+        let mut table = cs.add_table("Keccak chain");
+
+        let keccakf = Keccakf::new(&mut table);
+        let step_in = table.add_committed::<B64, 1>("step_in");
+        let step_out = table.add_committed::<B64, 1>("step_out");
+        table.assert_zero(
+            "step_out",
+            step_out - step_in * B64::MULTIPLICATIVE_GENERATOR,
+        );
+        table.pull(channel, once(step_in).chain(lanes(&keccakf.state_in)));
+        table.push(channel, once(step_out).chain(lanes(&keccakf.state_out)));
+
+        Self {
+            table_id: table.id(),
+            keccakf,
+            step_in,
+            step_out,
+        }
+    }
+}
+
+impl<P> TableFiller<P> for ChainTable
+where
+    P: PackedFieldIndexable<Scalar = B128>
+        + PackedExtension<B1>
+        + PackedExtension<B8>
+        + PackedExtension<B64>,
+    PackedSubfield<P, B8>: PackedTransformationFactory<PackedSubfield<P, B8>>,
+{
+    /// Step of the permutation and its input state.
+    type Event = (B64, StateMatrix<u64>);
+
+    fn id(&self) -> TableId {
+        self.table_id
+    }
+
+    fn fill(&self, rows: &[Self::Event], witness: &mut TableWitnessSegment<P>) -> Result<()> {
+        self.keccakf
+            .populate_state_in(witness, rows.iter().map(|(_, state)| state))?;
+        self.keccakf.populate(witness)?;
+        let mut step_in = witness.get_scalars_mut(self.step_in)?;
+        let mut step_out = witness.get_scalars_mut(self.step_out)?;
+        for ((step_in, step_out), &(step, _)) in
+            step_in.iter_mut().zip(step_out.iter_mut()).zip(rows)
+        {
+            *step_in = step;
+            *step_out = step * B64::MULTIPLICATIVE_GENERATOR;
+        }
+        Ok(())
+    }
+}
+
+/// Table of the first and last states of chains, pushing the first state of each chain at step `1`
+/// and pulling the last one at step `g^length`. They're private like the inputs of the other
+/// workloads, so no boundaries are needed.
+pub struct EndsTable {
+    table_id: TableId,
+    first: [Col<B64>; 25],
+    last: [Col<B64>; 25],
+}
+
+impl EndsTable {
+    pub fn new(cs: &mut ConstraintSystem, channel: ChannelId, length: usize) -> Self {
+        let mut table = cs.add_table("Keccak chain ends");
+
+        let first_step = table.add_constant("first_step", [B64::ONE]);
+        let last_step = table.add_constant("last_step", [step(length)]);
+        let first = core::array::from_fn(|idx| table.add_committed(format!("first[{idx}]")));
+        let last = core::array::from_fn(|idx| table.add_committed(format!("last[{idx}]")));
+        table.push(channel, once(first_step).chain(first));
+        table.pull(channel, once(last_step).chain(last));
+
+        Self {
+            table_id: table.id(),
+            first,
+            last,
+        }
+    }
+}
+
+impl<P> TableFiller<P> for EndsTable
+where
+    P: PackedFieldIndexable<Scalar = B128> + PackedExtension<B64>,
+{
+    /// First and last states of a chain, with lanes indexed by `x + 5 * y`.
+    type Event = ([u64; 25], [u64; 25]);
+
+    fn id(&self) -> TableId {
+        self.table_id
+    }
+
+    fn fill(&self, rows: &[Self::Event], witness: &mut TableWitnessSegment<P>) -> Result<()> {
+        for (idx, (&first, &last)) in self.first.iter().zip(&self.last).enumerate() {
+            let mut first = witness.get_scalars_mut(first)?;
+            let mut last = witness.get_scalars_mut(last)?;
+            for ((first, last), (first_state, last_state)) in
+                first.iter_mut().zip(last.iter_mut()).zip(rows)
+            {
+                *first = B64::new(first_state[idx]);
+                *last = B64::new(last_state[idx]);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct BiniusKeccakChain {
+    num_permutations: usize,
+    length: usize,
+    log_inv_rate: usize,
+    security_bits: usize,
+}
+
+impl BiniusKeccakChain {
+    fn constraint_system(&self) -> (ConstraintSystem, ChainTable, EndsTable) {
+        let mut cs = ConstraintSystem::new();
+        let channel = cs.add_channel("Keccak chain");
+        let chain_table = ChainTable::new(&mut cs, channel);
+        let ends_table = EndsTable::new(&mut cs, channel, self.length);
+        (cs, chain_table, ends_table)
+    }
+}
+
+impl HashInSnark for BiniusKeccakChain {
+    type Hash = KeccakF1600;
+    /// First states of the chains.
+    type Input = Vec<[u64; 25]>;
+    type Proof = Proof;
+    type Error = Error;
+
+    fn new(num_permutations: usize) -> Self
+    where
+        Self: Sized,
+    {
+        let num_permutations = num_permutations.next_power_of_two();
+        let length = chain_length();
+        assert!(
+            length > 0 && num_permutations % length == 0,
+            "number of permutations must be a multiple of the chain length {length}"
+        );
+        let pcs = pcs_params();
+        Self {
+            num_permutations,
+            length,
+            log_inv_rate: pcs.log_inv_rate,
+            security_bits: pcs.security_bits.unwrap_or(100),
+        }
+    }
+
+    fn num_permutations(&self) -> usize {
+        self.num_permutations
+    }
+
+    fn workload(&self) -> Workload {
+        Workload::Chains {
+            length: self.length,
+        }
+    }
+
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+        repeat_with(|| core::array::from_fn(|_| rng.next_u64()))
+            .take(self.num_permutations / self.length)
+            .collect()
+    }
+
+    fn prove(&self, input: Self::Input) -> Self::Proof {
+        let mut events = Vec::with_capacity(self.num_permutations);
+        let chains = input
+            .into_iter()
+            .map(|first| {
+                let mut state = first;
+                for idx in 0..self.length {
+                    let matrix = StateMatrix::from_fn(|(x, y)| state[x + 5 * y]);
+                    events.push((step(idx), matrix));
+                    keccakf(&mut state);
+                }
+                (first, state)
+            })
+            .collect::<Vec<_>>();
+
+        let mut allocator = CpuComputeAllocator::new(
+            1 << (11 + log2_ceil_usize(self.num_permutations)
+                - PackedType::<OptimalUnderlier, B128>::LOG_WIDTH),
+        );
+        let allocator = allocator.into_bump_allocator();
+        let (cs, chain_table, ends_table) = self.constraint_system();
+
+        let boundaries = vec![];
+        let table_sizes = vec![self.num_permutations, chains.len()];
+
+        let mut witness = WitnessIndex::<PackedType<OptimalUnderlier, B128>>::new(&cs, &allocator);
+        witness.fill_table_parallel(&chain_table, &events).unwrap();
+        witness.fill_table_parallel(&ends_table, &chains).unwrap();
+
+        let ccs = cs.compile().unwrap();
+        let cs_digest = ccs.digest::<Groestl256>();
+        let witness = witness.into_multilinear_extension_index();
+
+        let mut compute_holder =
+            FastCpuLayerHolder::<CanonicalTowerFamily, PackedType<OptimalUnderlier, B128>>::new(
+                1 << 20,
+                1 << (13 + log2_ceil_usize(self.num_permutations)),
+            );
+
+        let proof = constraint_system::prove::<
+            _,
+            OptimalUnderlier,
+            CanonicalTowerFamily,
+            Groestl256Parallel,
+            Groestl256ByteCompression,
+            HasherChallenger<Groestl256>,
+            _,
+            _,
+            _,
+        >(
+            &mut compute_holder.to_data(),
+            &ccs,
+            self.log_inv_rate,
+            self.security_bits,
+            &cs_digest,
+            &boundaries,
+            &table_sizes,
+            witness,
+            &make_portable_backend(),
+        )
+        .unwrap();
+
+        proof
+    }
+
+    fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error> {
+        let (cs, _, _) = self.constraint_system();
+        let boundaries = vec![];
+        let ccs = cs.compile().unwrap();
+        let cs_digest = ccs.digest::<Groestl256>();
+
+        binius_core::constraint_system::verify::<
+            OptimalUnderlier,
+            CanonicalTowerFamily,
+            CountedGroestl256,
+            CountedGroestl256ByteCompression,
+            HasherChallenger<CountedGroestl256>,
+        >(
+            &ccs,
+            self.log_inv_rate,
+            self.security_bits,
+            &cs_digest,
+            &boundaries,
+            proof.clone(),
+        )?;
+
+        Ok(())
+    }
+
+    fn describe(&self) -> Description {
+        describe(self.log_inv_rate, self.security_bits)
+    }
+
+    fn field_bits() -> u32 {
+        128
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(&proof.transcript)
+    }
+
+    fn decode_proof(data: &[u8], codec: Codec) -> Self::Proof {
+        let transcript = codec.decode(data);
+        Proof { transcript }
+    }
+}
@@ -1,13 +1,8 @@
 use bench::main;
-use bench_binius::{
-    hash::{BiniusGroestl, BiniusKeccak},
-    setup_trace,
-};
+use bench_binius::hash::{BiniusGroestl, BiniusKeccak, BiniusKeccakChain};
 
 main!(
-    setup_trace = setup_trace;
-    hash = {
-        Groestl => BiniusGroestl,
-        Keccak => BiniusKeccak,
-    };
+    Groestl => BiniusGroestl,
+    Keccak => BiniusKeccak,
+    KeccakChain => BiniusKeccakChain,
 );
@@ -1,22 +1,31 @@
-use bench::{criterion::bench, util::po2};
+use bench::{
+    alloc::TrackingAllocator,
+    criterion::{bench, bench_verify, Metric, PeakHeap, ProofSize},
+    util::{log_permutations, po2},
+};
 use bench_expander::{
     circuit::{Gf2Keccak, M31Poseidon},
     Expander,
 };
 use criterion::{criterion_group, criterion_main, Criterion};
 
-fn bench_keccak(c: &mut Criterion) {
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+fn bench_keccak<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("keccak");
 
     type H = Expander<Gf2Keccak>;
-    bench::<H>(&mut group, "raw", po2(10..13));
+    bench::<H, _>(&mut group, "raw", po2(log_permutations(10..13)));
+    bench_verify::<H, _>(&mut group, "raw", po2(log_permutations(10..13)));
 }
 
-fn bench_poseidon(c: &mut Criterion) {
+fn bench_poseidon<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("m31_poseidon");
 
     type H = Expander<M31Poseidon>;
-    bench::<H>(&mut group, "raw", po2(15..18));
+    bench::<H, _>(&mut group, "raw", po2(log_permutations(15..18)));
+    bench_verify::<H, _>(&mut group, "raw", po2(log_permutations(15..18)));
 }
 
 criterion_group!(
@@ -24,4 +33,14 @@ criterion_group!(
     config = Criterion::default().sample_size(10);
     targets = bench_keccak, bench_poseidon,
 );
-criterion_main!(benches);
+criterion_group!(
+    name = proof_size;
+    config = Criterion::default().with_measurement(ProofSize).sample_size(10).without_plots();
+    targets = bench_keccak, bench_poseidon,
+);
+criterion_group!(
+    name = peak_heap;
+    config = Criterion::default().with_measurement(PeakHeap).sample_size(10).without_plots();
+    targets = bench_keccak, bench_poseidon,
+);
+criterion_main!(benches, proof_size, peak_heap);
@@ -1,4 +1,5 @@
 use crate::ExpanderCircuit;
+use bench::hash::KeccakF1600;
 use expander_config::{GF2ExtConfigSha2, GKRScheme};
 
 pub struct Gf2Keccak;
@@ -6,8 +7,14 @@ pub struct Gf2Keccak;
 impl ExpanderCircuit for Gf2Keccak {
     const CIRCUIT_DIR: &str = "./circuit/gf2_keccak";
 
+    const FIELD: &str = "GF(2)";
+
+    const EXTENSION_DEGREE: usize = 128;
+
     type Config = GF2ExtConfigSha2;
 
+    type Hash = KeccakF1600;
+
     fn scheme() -> GKRScheme {
         GKRScheme::Vanilla
     }
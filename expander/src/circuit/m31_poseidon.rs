@@ -1,14 +1,33 @@
 use crate::ExpanderCircuit;
-use expander_config::{GKRScheme, M31ExtConfigSha2};
+use expander_arith::FieldSerde;
+use expander_config::{GKRConfig, GKRScheme, M31ExtConfigSha2};
+use rand::RngCore;
+
+/// Modulus of M31.
+const MODULUS: u32 = (1 << 31) - 1;
 
 pub struct M31Poseidon;
 
 impl ExpanderCircuit for M31Poseidon {
     const CIRCUIT_DIR: &str = "./circuit/m31_poseidon";
 
+    const FIELD: &str = "M31";
+
+    const EXTENSION_DEGREE: usize = 3;
+
     type Config = M31ExtConfigSha2;
 
+    type Hash = bench::hash::M31Poseidon;
+
     fn scheme() -> GKRScheme {
         GKRScheme::Vanilla
     }
+
+    /// Lanes of little-endian words reduced to canonical M31 elements.
+    fn input_value(rng: &mut impl RngCore) -> Vec<u8> {
+        let size = <<Self::Config as GKRConfig>::SimdCircuitField as FieldSerde>::SERIALIZED_SIZE;
+        (0..size / 4)
+            .flat_map(|_| (rng.next_u32() % MODULUS).to_le_bytes())
+            .collect()
+    }
 }
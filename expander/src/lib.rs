@@ -1,4 +1,4 @@
-use bench::HashInSnark;
+use bench::{codec::Codec, hash::HashDescriptor, report::Description, HashInSnark};
 use expander_arith::FieldSerde;
 use expander_circuit::Circuit;
 use expander_config::{Config, FiatShamirHashType, GKRConfig, GKRScheme};
@@ -6,26 +6,59 @@ use expander_gkr::{gkr_verify, Prover};
 use expander_transcript::{BytesHashTranscript, Keccak256hasher, SHA256hasher, Transcript};
 use rand::RngCore;
 use rayon::{current_num_threads, prelude::*};
-use std::{cell::RefCell, io::Cursor, iter::repeat_with};
+use std::{io::Cursor, iter::repeat_with, sync::Mutex};
 
 pub mod circuit;
 
 pub trait ExpanderCircuit {
     const CIRCUIT_DIR: &str;
 
+    /// Name of the base field of the circuit in [`Description`].
+    const FIELD: &str;
+
+    /// Degree of the field of challenges over [`Self::FIELD`].
+    const EXTENSION_DEGREE: usize;
+
     type Config: GKRConfig;
 
+    type Hash: HashDescriptor;
+
     fn scheme() -> GKRScheme;
+
+    /// Returns a value of the input layer drawn from `rng`, serialized as read by
+    /// [`FieldSerde::deserialize_from`], by default from its bytes as drawn.
+    fn input_value(rng: &mut impl RngCore) -> Vec<u8> {
+        let mut bytes =
+            vec![0; <<Self::Config as GKRConfig>::SimdCircuitField as FieldSerde>::SERIALIZED_SIZE];
+        rng.fill_bytes(&mut bytes);
+        bytes
+    }
 }
 
 pub struct Expander<C: ExpanderCircuit> {
     num_permutations: usize,
     config: Config<C::Config>,
     circuit: Circuit<C::Config>,
-    provers: RefCell<Vec<Prover<C::Config>>>,
+    num_threads: usize,
+    /// Sets of a prover per thread, each taken by a `prove` and returned after, so concurrent
+    /// proofs don't share provers.
+    provers: Mutex<Vec<Vec<Prover<C::Config>>>>,
+}
+
+impl<C: ExpanderCircuit> Expander<C> {
+    fn new_provers(&self) -> Vec<Prover<C::Config>> {
+        repeat_with(|| {
+            let mut prover = Prover::new(&self.config);
+            prover.prepare_mem(&self.circuit);
+            prover
+        })
+        .take(self.num_threads)
+        .collect()
+    }
 }
 
 impl<C: ExpanderCircuit> HashInSnark for Expander<C> {
+    type Hash = C::Hash;
     type Input = Vec<Circuit<C::Config>>;
     type Proof = Vec<(<C::Config as GKRConfig>::ChallengeField, Vec<u8>)>;
     type Error = ();
@@ -47,46 +80,55 @@ impl<C: ExpanderCircuit> HashInSnark for Expander<C> {
         );
         let circuit = Circuit::load_circuit(&circuit_path);
         let config = Config::new(C::scheme(), Default::default());
-        let provers = repeat_with(|| {
-            let mut prover = Prover::new(&config);
-            prover.prepare_mem(&circuit);
-            prover
-        })
-        .take(current_num_threads)
-        .collect::<Vec<_>>();
-        Self {
+        let snark = Self {
             num_permutations,
             config,
             circuit,
-            provers: provers.into(),
-        }
+            num_threads: current_num_threads,
+            provers: Default::default(),
+        };
+        let provers = snark.new_provers();
+        snark.provers.lock().unwrap().push(provers);
+        snark
     }
 
     fn num_permutations(&self) -> usize {
         self.num_permutations
     }
 
-    fn generate_input(&self, _: impl RngCore) -> Self::Input {
+    /// Fills the input layer of a circuit per thread from `rng`, as `set_random_input_for_test`
+    /// does from a thread-local one.
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
         repeat_with(|| {
             let mut circuit = self.circuit.clone();
-            circuit.set_random_input_for_test();
+            let layer = &mut circuit.layers[0];
+            layer.input_vals = repeat_with(|| {
+                let value = C::input_value(&mut rng);
+                <C::Config as GKRConfig>::SimdCircuitField::deserialize_from(value.as_slice())
+                    .unwrap()
+            })
+            .take(1 << layer.input_var_num)
+            .collect();
             circuit.evaluate();
             circuit
         })
-        .take(self.provers.borrow().len())
+        .take(self.num_threads)
         .collect()
     }
 
     fn prove(&self, circuits: Self::Input) -> Self::Proof {
-        self.provers
-            .borrow_mut()
+        let popped = self.provers.lock().unwrap().pop();
+        let mut provers = popped.unwrap_or_else(|| self.new_provers());
+        let proof = provers
             .par_iter_mut()
             .zip(circuits.into_par_iter())
             .map(|(prover, mut circuit)| {
                 let (claimed_v, transcript) = prover.prove(&mut circuit);
                 (claimed_v, transcript.bytes)
             })
-            .collect()
+            .collect();
+        self.provers.lock().unwrap().push(provers);
+        proof
     }
 
     fn verify(&self, proofs: &Self::Proof) -> Result<(), Self::Error> {
@@ -126,17 +168,42 @@ impl<C: ExpanderCircuit> HashInSnark for Expander<C> {
             .ok_or(())
     }
 
-    fn serialize_proof(proofs: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(&proofs.iter().map(|(claimed_v, proof)| {
+    fn describe(&self) -> Description {
+        let transcript_hash = match C::Config::FIAT_SHAMIR_HASH {
+            FiatShamirHashType::Keccak256 => "Keccak-256".to_string(),
+            FiatShamirHashType::SHA256 => "SHA-256".to_string(),
+            hash => format!("{hash:?}"),
+        };
+        Description {
+            field: C::FIELD.to_string(),
+            extension_degree: C::EXTENSION_DEGREE,
+            arithmetization: "GKR".to_string(),
+            pcs: "raw".to_string(),
+            merkle_hash: None,
+            transcript_hash,
+            log_inv_rate: None,
+            num_queries: None,
+            security_bits: None,
+            pow_bits: None,
+        }
+    }
+
+    /// Proofs are transcripts of bytes, so packing only saves on lengths.
+    fn field_bits() -> u32 {
+        8 * <<C as ExpanderCircuit>::Config as GKRConfig>::ChallengeField::SERIALIZED_SIZE as u32
+    }
+
+    fn encode_proof(proofs: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(&proofs.iter().map(|(claimed_v, proof)| {
             let mut claimed_v_bytes =
                 vec![0; <<C as ExpanderCircuit>::Config as GKRConfig>::ChallengeField::SERIALIZED_SIZE];
             claimed_v.serialize_into(&mut claimed_v_bytes).unwrap();
             (claimed_v_bytes, proof)
-        }).collect::<Vec<_>>()).unwrap()
+        }).collect::<Vec<_>>())
     }
 
-    fn deserialize_proof(bytes: &[u8]) -> Self::Proof {
-        let proofs: Vec<(Vec<u8>, Vec<u8>)> = bincode::deserialize(bytes).unwrap();
+    fn decode_proof(bytes: &[u8], codec: Codec) -> Self::Proof {
+        let proofs: Vec<(Vec<u8>, Vec<u8>)> = codec.decode(bytes);
         proofs
             .into_iter()
             .map(|(claimed_v_bytes, proof)| {
@@ -1,10 +1,10 @@
 // Copied and modified from https://github.com/morgana-proofs/hashcaster/blob/d9891c0/src/examples/keccak/main_protocol.rs.
 
 use crate::util::{
-    deserialize_packed, serialize_packed, BatchFRIPCS128, Error, F128Challenger, FriPcsProof,
-    SumcheckError, SumcheckProof,
+    deserialize_packed, serialize_packed, BatchFRIPCS128, CountedCompression, CountedKeccak256Hash,
+    Error, F128Challenger, FriPcsProof, SumcheckError, SumcheckProof,
 };
-use bench::{util::pcs_log_inv_rate, HashInSnark};
+use bench::{codec::Codec, hash::KeccakF1600, report::Description, util::pcs_params, HashInSnark};
 use binius_core::tower::{AESTowerFamily, TowerFamily};
 use binius_field::{arch::OptimalUnderlier, PackedField};
 use binius_hash::{Groestl256, GroestlDigest, GroestlDigestCompression};
@@ -38,18 +38,23 @@ const LIN_CHECK_NUM_VARS: usize = 10;
 type U = OptimalUnderlier;
 type Tower = AESTowerFamily;
 type DomainFactory = IsomorphicEvaluationDomainFactory<<Tower as TowerFamily>::B8>;
+type Compression = GroestlDigestCompression<<Tower as TowerFamily>::B8>;
+type Pcs<Compress> = BatchFRIPCS128<
+    Tower,
+    U,
+    GroestlDigest<<Tower as TowerFamily>::B8>,
+    DomainFactory,
+    Groestl256<<Tower as TowerFamily>::B128, <Tower as TowerFamily>::B8>,
+    Compress,
+>;
 
-#[allow(clippy::type_complexity)]
 pub struct HashcasterKeccak {
     num_permutations: usize,
-    pcs: BatchFRIPCS128<
-        Tower,
-        U,
-        GroestlDigest<<Tower as TowerFamily>::B8>,
-        DomainFactory,
-        Groestl256<<Tower as TowerFamily>::B128, <Tower as TowerFamily>::B8>,
-        GroestlDigestCompression<<Tower as TowerFamily>::B8>,
-    >,
+    log_inv_rate: usize,
+    security_bits: usize,
+    pcs: Pcs<Compression>,
+    /// PCS of verifiers, counting Merkle compressions for [`bench::cost`].
+    verifier_pcs: Pcs<CountedCompression<Compression>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -65,6 +70,7 @@ pub struct HashcasterKeccakProof {
 }
 
 impl HashInSnark for HashcasterKeccak {
+    type Hash = KeccakF1600;
     type Input = [Vec<F128>; 5];
     type Proof = HashcasterKeccakProof;
     type Error = Error;
@@ -75,12 +81,16 @@ impl HashInSnark for HashcasterKeccak {
     {
         let num_vars = (num_permutations.ilog2() as usize + NUM_VARS_PER_PERMUTATIONS).max(10);
         let num_permutations = 3 << (num_vars - 3);
-        let security_bits = 100;
-        let log_inv_rate = pcs_log_inv_rate();
-        let pcs = BatchFRIPCS128::new(security_bits, log_inv_rate, num_vars, 5);
+        let params = pcs_params();
+        let security_bits = params.security_bits.unwrap_or(100);
+        let pcs = BatchFRIPCS128::new(security_bits, params.log_inv_rate, num_vars, 5);
+        let verifier_pcs = BatchFRIPCS128::new(security_bits, params.log_inv_rate, num_vars, 5);
         Self {
             num_permutations,
+            log_inv_rate: params.log_inv_rate,
+            security_bits,
             pcs,
+            verifier_pcs,
         }
     }
 
@@ -155,7 +165,7 @@ impl HashInSnark for HashcasterKeccak {
     }
 
     fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error> {
-        let mut challenger = F128Challenger::keccak256();
+        let mut challenger = F128Challenger::counted_keccak256();
 
         proof
             .input_comm
@@ -188,16 +198,35 @@ impl HashInSnark for HashcasterKeccak {
             claims = lin_check_proof.evals.clone().try_into().unwrap();
         }
 
-        self.pcs
+        self.verifier_pcs
             .verify(&proof.input_comm, &proof.input_open_proof, &point, &claims)
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(proof).unwrap()
+    fn describe(&self) -> Description {
+        Description {
+            field: "GF(2) AES tower".to_string(),
+            extension_degree: 128,
+            arithmetization: "boolcheck and lincheck sumchecks".to_string(),
+            pcs: "FRI-Binius".to_string(),
+            merkle_hash: Some("Groestl-256".to_string()),
+            transcript_hash: "Keccak-256".to_string(),
+            log_inv_rate: Some(self.log_inv_rate),
+            num_queries: None,
+            security_bits: Some(self.security_bits),
+            pow_bits: None,
+        }
+    }
+
+    fn field_bits() -> u32 {
+        128
     }
 
-    fn deserialize_proof(bytes: &[u8]) -> Self::Proof {
-        bincode::deserialize(bytes).unwrap()
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(proof)
+    }
+
+    fn decode_proof(bytes: &[u8], codec: Codec) -> Self::Proof {
+        codec.decode(bytes)
     }
 }
 
@@ -330,7 +359,7 @@ impl HashcasterKeccak {
         claims: &[F128; 5],
         bool_check_proof: &SumcheckProof,
         multi_open_proof: &SumcheckProof,
-        challenger: &mut F128Challenger,
+        challenger: &mut F128Challenger<CountedKeccak256Hash>,
     ) -> Result<Vec<F128>, SumcheckError> {
         assert_eq!(bool_check_proof.round_polys.len(), self.num_vars());
         assert_eq!(multi_open_proof.round_polys.len(), self.num_vars());
@@ -424,7 +453,7 @@ impl HashcasterKeccak {
         point: &[F128],
         claims: &[F128; 5],
         lin_check_proof: &SumcheckProof,
-        challenger: &mut F128Challenger,
+        challenger: &mut F128Challenger<CountedKeccak256Hash>,
     ) -> Result<Vec<F128>, SumcheckError> {
         assert_eq!(lin_check_proof.round_polys.len(), LIN_CHECK_NUM_VARS);
         assert_eq!(lin_check_proof.evals.len(), 5);
@@ -1,3 +1,7 @@
+use bench::{
+    cost::{self, CountedDigest},
+    hash::{GroestlP, HashDescriptor, KeccakF1600},
+};
 use binius_core::{
     fiat_shamir::HasherChallenger,
     merkle_tree_vcs::{BinaryMerkleTreeProver, BinaryMerkleTreeScheme},
@@ -58,6 +62,40 @@ pub struct SumcheckProof {
     pub evals: Vec<F128>,
 }
 
+/// [`Keccak256Hash`] counting hash calls for [`bench::cost`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountedKeccak256Hash;
+
+impl CryptographicHasher<u8, [u8; 32]> for CountedKeccak256Hash {
+    fn hash_iter<I>(&self, input: I) -> [u8; 32]
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let input = input.into_iter().collect::<Vec<_>>();
+        self.hash_slice(&input)
+    }
+
+    fn hash_slice(&self, input: &[u8]) -> [u8; 32] {
+        cost::count_hash_of::<KeccakF1600>(input.len());
+        Keccak256Hash.hash_slice(input)
+    }
+}
+
+/// Compression function counting calls for [`bench::cost`], as a 2-to-1 compression of
+/// [`GroestlP`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountedCompression<C>(C);
+
+impl<T, const N: usize, C: PseudoCompressionFunction<T, N>> PseudoCompressionFunction<T, N>
+    for CountedCompression<C>
+{
+    fn compress(&self, input: [T; N]) -> T {
+        cost::count_hash(size_of::<[T; N]>());
+        cost::count_permutations(GroestlP::PERMUTATIONS_PER_COMPRESSION);
+        self.0.compress(input)
+    }
+}
+
 pub struct F128Challenger<H: CryptographicHasher<u8, [u8; 32]> = Keccak256Hash> {
     inner: HashChallenger<u8, H, 32>,
 }
@@ -79,6 +117,13 @@ impl F128Challenger<Keccak256Hash> {
     }
 }
 
+impl F128Challenger<CountedKeccak256Hash> {
+    /// Returns the challenger of verifiers, counting hashes for [`bench::cost`].
+    pub fn counted_keccak256() -> Self {
+        Self::new(Vec::new(), CountedKeccak256Hash)
+    }
+}
+
 impl<H> CanObserve<F128> for F128Challenger<H>
 where
     H: CryptographicHasher<u8, [u8; 32]>,
@@ -271,8 +316,9 @@ where
         point: &[F128],
         evals: &[F128],
     ) -> Result<(), Error> {
-        let mut transcript =
-            TranscriptReader::<HasherChallenger<Groestl256>>::new(proof.transcript.clone());
+        let mut transcript = TranscriptReader::<
+            HasherChallenger<CountedDigest<Groestl256, GroestlP>>,
+        >::new(proof.transcript.clone());
         let mut advice = AdviceReader::new(proof.advice.clone());
         let point = iso_slice(point);
         let evals = iso_slice(evals);
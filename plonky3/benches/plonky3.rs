@@ -1,4 +1,8 @@
-use bench::{criterion::bench, util::po2};
+use bench::{
+    alloc::TrackingAllocator,
+    criterion::{bench, bench_verify, Metric, PeakHeap, ProofSize},
+    util::{log_permutations, po2},
+};
 use bench_plonky3::{
     circuit::{Blake3Circuit, KeccakCircuit, KoalaBearPoseidon2Circuit},
     config::{BabyBearKeccakMtConfig, KoalaBearKeccakMtConfig},
@@ -6,25 +10,31 @@ use bench_plonky3::{
 };
 use criterion::{criterion_group, criterion_main, Criterion};
 
-fn bench_keccak(c: &mut Criterion) {
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+fn bench_keccak<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("keccak");
 
     type H = Plonky3<BabyBearKeccakMtConfig, KeccakCircuit>;
-    bench::<H>(&mut group, "baby_bear_keccak_mt", po2(10..13));
+    bench::<H, _>(&mut group, "baby_bear_keccak_mt", po2(log_permutations(10..13)));
+    bench_verify::<H, _>(&mut group, "baby_bear_keccak_mt", po2(log_permutations(10..13)));
 }
 
-fn bench_blake3(c: &mut Criterion) {
+fn bench_blake3<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("blake3");
 
     type H = Plonky3<BabyBearKeccakMtConfig, Blake3Circuit>;
-    bench::<H>(&mut group, "baby_bear_keccak_mt", po2(10..13));
+    bench::<H, _>(&mut group, "baby_bear_keccak_mt", po2(log_permutations(10..13)));
+    bench_verify::<H, _>(&mut group, "baby_bear_keccak_mt", po2(log_permutations(10..13)));
 }
 
-fn bench_koala_bear_poseidon2(c: &mut Criterion) {
+fn bench_koala_bear_poseidon2<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("koala_bear_poseidon2");
 
     type H = Plonky3<KoalaBearKeccakMtConfig, KoalaBearPoseidon2Circuit>;
-    bench::<H>(&mut group, "koala_bear_keccak_mt", po2(15..18));
+    bench::<H, _>(&mut group, "koala_bear_keccak_mt", po2(log_permutations(15..18)));
+    bench_verify::<H, _>(&mut group, "koala_bear_keccak_mt", po2(log_permutations(15..18)));
 }
 
 criterion_group!(
@@ -32,4 +42,14 @@ criterion_group!(
     config = Criterion::default().sample_size(10);
     targets = bench_keccak, bench_blake3, bench_koala_bear_poseidon2,
 );
-criterion_main!(benches);
+criterion_group!(
+    name = proof_size;
+    config = Criterion::default().with_measurement(ProofSize).sample_size(10).without_plots();
+    targets = bench_keccak, bench_blake3, bench_koala_bear_poseidon2,
+);
+criterion_group!(
+    name = peak_heap;
+    config = Criterion::default().with_measurement(PeakHeap).sample_size(10).without_plots();
+    targets = bench_keccak, bench_blake3, bench_koala_bear_poseidon2,
+);
+criterion_main!(benches, proof_size, peak_heap);
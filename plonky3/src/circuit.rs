@@ -1,5 +1,6 @@
-use p3_air::Air;
-use p3_matrix::dense::RowMajorMatrix;
+use bench::{hash::HashDescriptor, workload::Workload};
+use p3_air::{Air, AirBuilder};
+use p3_matrix::{dense::RowMajorMatrix, horizontally_truncated::HorizontallyTruncated};
 use p3_uni_stark::{
     ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, Val, VerifierConstraintFolder,
 };
@@ -9,31 +10,74 @@ use rand::RngCore;
 use p3_uni_stark::DebugConstraintBuilder;
 
 mod blake3;
+mod chain;
 mod keccak;
 mod koala_bear_poseidon2;
+mod merkle;
 
 pub use blake3::Blake3Circuit;
+pub use chain::{Blake3Step, ChainAir, ChainCircuit, KeccakStep, KoalaBearPoseidon2Step, Step};
 pub use keccak::KeccakCircuit;
 pub use koala_bear_poseidon2::KoalaBearPoseidon2Circuit;
+pub use merkle::{
+    Compression, KeccakCompression, KoalaBearPoseidon2Compression, MerkleAir, MerkleCircuit,
+};
 
-pub trait Plonky3Circuit<SC: StarkGenericConfig> {
-    #[cfg(debug_assertions)]
-    type Air: Air<SymbolicAirBuilder<Val<SC>>>
+/// AIR provable and verifiable by `p3_uni_stark` with `SC`.
+#[cfg(debug_assertions)]
+pub trait Plonky3Air<SC: StarkGenericConfig>:
+    Air<SymbolicAirBuilder<Val<SC>>>
+    + for<'a> Air<ProverConstraintFolder<'a, SC>>
+    + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>>>
+    + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+{
+}
+
+#[cfg(debug_assertions)]
+impl<SC: StarkGenericConfig, A> Plonky3Air<SC> for A where
+    A: Air<SymbolicAirBuilder<Val<SC>>>
         + for<'a> Air<ProverConstraintFolder<'a, SC>>
         + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>>>
-        + for<'a> Air<VerifierConstraintFolder<'a, SC>>;
-    #[cfg(not(debug_assertions))]
-    type Air: Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+{
+}
+
+/// AIR provable and verifiable by `p3_uni_stark` with `SC`.
+#[cfg(not(debug_assertions))]
+pub trait Plonky3Air<SC: StarkGenericConfig>:
+    Air<SymbolicAirBuilder<Val<SC>>>
+    + for<'a> Air<ProverConstraintFolder<'a, SC>>
+    + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+{
+}
+
+#[cfg(not(debug_assertions))]
+impl<SC: StarkGenericConfig, A> Plonky3Air<SC> for A where
+    A: Air<SymbolicAirBuilder<Val<SC>>>
         + for<'a> Air<ProverConstraintFolder<'a, SC>>
-        + for<'a> Air<VerifierConstraintFolder<'a, SC>>;
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+{
+}
+
+pub trait Plonky3Circuit<SC: StarkGenericConfig> {
+    type Air: Plonky3Air<SC>;
+    type Hash: HashDescriptor;
     type Input;
 
+    /// Whether [`Self::public_values`] returns any, which are then serialized with proofs.
+    const HAS_PUBLIC_VALUES: bool = false;
+
     fn new(num_permutations: usize, log_blowup: usize) -> Self
     where
         Self: Sized;
 
     fn num_permutations(&self) -> usize;
 
+    /// Computation proven, see `HashInSnark::workload`.
+    fn workload(&self) -> Workload {
+        Workload::Permutations
+    }
+
     fn trace_height(&self) -> usize;
 
     fn air(&self) -> &Self::Air;
@@ -41,4 +85,44 @@ pub trait Plonky3Circuit<SC: StarkGenericConfig> {
     fn generate_input(&self, rng: impl RngCore) -> Self::Input;
 
     fn generate_trace(&self, input: Self::Input) -> RowMajorMatrix<Val<SC>>;
+
+    /// Returns the public values of `trace`, e.g. the root of a Merkle tree.
+    fn public_values(&self, _trace: &RowMajorMatrix<Val<SC>>) -> Vec<Val<SC>> {
+        Vec::new()
+    }
+}
+
+/// Builder of the AIR of a permutation, restricted to its columns first in rows of a wider AIR,
+/// e.g. [`MerkleAir`] or [`ChainAir`].
+pub struct Prefix<'a, AB> {
+    builder: &'a mut AB,
+    width: usize,
+}
+
+impl<AB: AirBuilder> AirBuilder for Prefix<'_, AB> {
+    type F = AB::F;
+    type Expr = AB::Expr;
+    type Var = AB::Var;
+    type M = HorizontallyTruncated<AB::Var, AB::M>;
+
+    fn main(&self) -> Self::M {
+        HorizontallyTruncated::new(self.builder.main(), self.width)
+            .expect("permutation is narrower than the row")
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.builder.is_first_row()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.builder.is_last_row()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        self.builder.is_transition_window(size)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.builder.assert_zero(x);
+    }
 }
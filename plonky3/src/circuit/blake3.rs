@@ -1,4 +1,5 @@
 use crate::Plonky3Circuit;
+use bench::hash::Blake3;
 use p3_blake3_air::{Blake3Air, generate_trace_rows};
 use p3_field::PrimeField64;
 use p3_matrix::dense::RowMajorMatrix;
@@ -16,6 +17,7 @@ where
     Val<SC>: PrimeField64,
 {
     type Air = Blake3Air;
+    type Hash = Blake3;
     type Input = Vec<[u32; 24]>;
 
     fn new(num_permutations: usize, log_blowup: usize) -> Self
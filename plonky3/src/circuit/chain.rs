@@ -0,0 +1,369 @@
+use crate::{
+    Plonky3Circuit,
+    circuit::{Plonky3Air, Prefix},
+};
+use bench::{hash::HashDescriptor, util::chain_length, workload::Workload};
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_matrix::{Matrix, dense::RowMajorMatrix};
+use p3_uni_stark::{StarkGenericConfig, Val};
+use rand::RngCore;
+
+mod blake3;
+mod keccak;
+mod koala_bear_poseidon2;
+
+pub use blake3::Blake3Step;
+pub use keccak::KeccakStep;
+pub use koala_bear_poseidon2::KoalaBearPoseidon2Step;
+
+/// Permutation hashing the digest of the previous one in a chain, proven by the AIR of the
+/// permutation with its columns first in rows of [`ChainAir`].
+pub trait Step<F: Field>: Sync {
+    type Air: BaseAir<F> + Sync;
+    type Hash: HashDescriptor;
+
+    /// Number of field elements of a digest.
+    const DIGEST: usize;
+
+    /// Number of permutations side by side in a row, each of a different chain.
+    const LANES: usize;
+
+    /// Number of rows of a permutation.
+    const ROWS: usize;
+
+    fn new() -> Self;
+
+    fn air(&self) -> &Self::Air;
+
+    /// Returns for each element of the input digest of each lane the input columns with their
+    /// coefficients summing to it, which are constant over the rows of a permutation.
+    fn input(&self) -> &[Vec<(usize, u64)>];
+
+    /// Returns the columns of the output digest of each lane in the last row of a permutation.
+    fn output(&self) -> &[usize];
+
+    /// Returns the input columns fixed to constants, e.g. padding of a sponge.
+    fn fixed(&self) -> &[(usize, u64)];
+
+    /// Returns the input columns with their coefficients summing to the index of the row of
+    /// permutations in the trace of [`Self::generate_trace`] and to the number of these rows, if
+    /// the permutation hashes them.
+    fn position(&self) -> Option<(&[(usize, u64)], &[(usize, u64)])>;
+
+    /// Returns the column being one in the last row of a permutation, if it takes several rows.
+    fn last_row(&self) -> Option<usize>;
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<F>;
+
+    /// Returns the trace of hashing each of `digests`, numbering a power of two and at least
+    /// [`Self::LANES`], in order from the first row and lane.
+    fn generate_trace(&self, digests: &[F], extra_capacity_bits: usize) -> RowMajorMatrix<F>;
+}
+
+/// Columns of [`ChainAir`] after the columns of the permutation, relative to them. They are
+/// constant over the rows of a permutation.
+struct Layout {
+    /// Number of columns of the permutation.
+    width: usize,
+    /// Number of permutations of a chain.
+    length: usize,
+    /// Number of rows of chains side by side in [`Step::LANES`].
+    groups: usize,
+}
+
+impl Layout {
+    /// Whether the permutation is of a chain rather than padding.
+    const IS_REAL: usize = 0;
+    /// Whether the permutation ends its chain, or is padding.
+    const IS_END: usize = 1;
+    /// Number of permutations left in the chain after this one.
+    const REMAINING: usize = 2;
+    /// Index of the group of chains, counting padding permutations after the last one.
+    const GROUP: usize = 3;
+
+    const NUM_COLUMNS: usize = 4;
+
+    fn column(&self, column: usize) -> usize {
+        self.width + column
+    }
+}
+
+/// AIR hashing chains of digests by permutations of `S`, each of `length` permutations where the
+/// input of a permutation is constrained to be the output digest of the previous one, as in
+/// verification of WOTS signatures. The permutations of a chain are in consecutive rows, and a
+/// counter of the permutations left in the chain selects the linking constraints.
+pub struct ChainAir<S> {
+    step: S,
+    layout: Layout,
+}
+
+impl<S> ChainAir<S> {
+    pub fn new<F: Field>(step: S, length: usize, num_chains: usize) -> Self
+    where
+        S: Step<F>,
+    {
+        let layout = Layout {
+            width: BaseAir::<F>::width(step.air()),
+            length,
+            groups: num_chains / S::LANES,
+        };
+        Self { step, layout }
+    }
+}
+
+impl<F: Field, S: Step<F>> BaseAir<F> for ChainAir<S> {
+    fn width(&self) -> usize {
+        self.layout.width + Layout::NUM_COLUMNS
+    }
+}
+
+impl<AB, S> Air<AB> for ChainAir<S>
+where
+    AB: AirBuilder,
+    S: Step<AB::F>,
+    S::Air: for<'a> Air<Prefix<'a, AB>>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let layout = &self.layout;
+        self.step.air().eval(&mut Prefix {
+            builder,
+            width: layout.width,
+        });
+
+        let main = builder.main();
+        let local_row = main.row_slice(0).expect("The matrix is empty?");
+        let next_row = main.row_slice(1).expect("The matrix only has 1 row?");
+        let local = |column: usize| -> AB::Expr { local_row[layout.column(column)].into() };
+        let next = |column: usize| -> AB::Expr { next_row[layout.column(column)].into() };
+        let local_inner = |column: usize| -> AB::Expr { local_row[column].into() };
+        let next_inner = |column: usize| -> AB::Expr { next_row[column].into() };
+
+        let is_real = local(Layout::IS_REAL);
+        let is_end = local(Layout::IS_END);
+        builder.assert_bool(is_real.clone());
+        builder.assert_bool(is_end.clone());
+        builder
+            .when(AB::Expr::ONE - is_real.clone())
+            .assert_zero(local(Layout::REMAINING));
+        builder
+            .when(is_end.clone())
+            .assert_zero(local(Layout::REMAINING));
+        for &(column, value) in self.step.fixed() {
+            builder
+                .when(is_real.clone())
+                .assert_eq(local_inner(column), AB::Expr::from_u64(value));
+        }
+        // Each position of the chains is hashed in a trace with a group in each row.
+        if let Some((index, rows)) = self.step.position() {
+            let sum = |terms: &[(usize, u64)]| {
+                terms
+                    .iter()
+                    .map(|&(column, coefficient)| {
+                        local_inner(column) * AB::Expr::from_u64(coefficient)
+                    })
+                    .sum::<AB::Expr>()
+            };
+            let num_rows = (layout.groups * S::LANES).next_power_of_two() / S::LANES;
+            let mut real = builder.when(is_real.clone());
+            real.assert_eq(sum(index), local(Layout::GROUP));
+            real.assert_eq(sum(rows), AB::Expr::from_usize(num_rows));
+        }
+
+        // Chains start from the first row, and the last row, if not padding, ends the last one.
+        let mut first_row = builder.when_first_row();
+        first_row.assert_one(is_real.clone());
+        first_row.assert_eq(
+            local(Layout::REMAINING),
+            AB::Expr::from_usize(layout.length - 1),
+        );
+        first_row.assert_zero(local(Layout::GROUP));
+        let mut last_row = builder.when_last_row();
+        let mut last_row = last_row.when(is_real.clone());
+        last_row.assert_one(is_end.clone());
+        last_row.assert_eq(
+            local(Layout::GROUP),
+            AB::Expr::from_usize(layout.groups - 1),
+        );
+
+        let boundary = match self.step.last_row() {
+            Some(column) => {
+                let mut within = builder.when_transition();
+                let mut within = within.when(AB::Expr::ONE - local_inner(column));
+                for column in 0..Layout::NUM_COLUMNS {
+                    within.assert_eq(next(column), local(column));
+                }
+                local_inner(column)
+            }
+            None => AB::Expr::ONE,
+        };
+
+        let mut transition = builder.when_transition();
+        let mut transition = transition.when(boundary);
+        let next_is_real = next(Layout::IS_REAL);
+        transition.assert_zero(next_is_real.clone() * (AB::Expr::ONE - is_real.clone()));
+        transition
+            .when(is_real - next_is_real.clone())
+            .assert_eq(next(Layout::GROUP), AB::Expr::from_usize(layout.groups));
+
+        // Within a chain, the next permutation hashes the output of this one.
+        let mut within = transition.when(AB::Expr::ONE - is_end.clone());
+        within.assert_eq(
+            next(Layout::REMAINING),
+            local(Layout::REMAINING) - AB::Expr::ONE,
+        );
+        within.assert_eq(next(Layout::GROUP), local(Layout::GROUP));
+        let mut link = transition.when(local(Layout::REMAINING));
+        for (terms, &output) in self.step.input().iter().zip(self.step.output()) {
+            let input = terms
+                .iter()
+                .map(|&(column, coefficient)| next_inner(column) * AB::Expr::from_u64(coefficient))
+                .sum::<AB::Expr>();
+            link.assert_eq(input, local_inner(output));
+        }
+
+        // After the end of a chain, the next one starts.
+        let mut end = transition.when(is_end);
+        end.assert_eq(
+            next(Layout::REMAINING),
+            next_is_real * AB::Expr::from_usize(layout.length - 1),
+        );
+        end.assert_eq(next(Layout::GROUP), local(Layout::GROUP) + AB::Expr::ONE);
+    }
+}
+
+/// Circuit proving chains of [`chain_length`] permutations, as many in total as it's set up with.
+pub struct ChainCircuit<S> {
+    air: ChainAir<S>,
+    num_permutations: usize,
+    log_blowup: usize,
+}
+
+impl<S> ChainCircuit<S> {
+    /// Returns the row of the output of the `idx`-th row of permutations in `trace`.
+    fn output_row<F: Field>(trace: &RowMajorMatrix<F>, idx: usize) -> &[F]
+    where
+        S: Step<F>,
+    {
+        let width = trace.width();
+        &trace.values[(S::ROWS * (idx + 1) - 1) * width..][..width]
+    }
+
+    fn num_chains(&self) -> usize {
+        self.num_permutations / self.air.layout.length
+    }
+}
+
+impl<SC, S> Plonky3Circuit<SC> for ChainCircuit<S>
+where
+    SC: StarkGenericConfig,
+    S: Step<Val<SC>>,
+    ChainAir<S>: Plonky3Air<SC>,
+{
+    type Air = ChainAir<S>;
+    type Hash = S::Hash;
+    /// Digests starting the chains.
+    type Input = Vec<Val<SC>>;
+
+    fn new(num_permutations: usize, log_blowup: usize) -> Self
+    where
+        Self: Sized,
+    {
+        let length = chain_length();
+        assert!(
+            length > 0 && num_permutations % (length * S::LANES) == 0,
+            "number of permutations must be a multiple of {} chains of length {length}",
+            S::LANES,
+        );
+        Self {
+            air: ChainAir::new::<Val<SC>>(S::new(), length, num_permutations / length),
+            num_permutations,
+            log_blowup,
+        }
+    }
+
+    fn num_permutations(&self) -> usize {
+        self.num_permutations
+    }
+
+    fn workload(&self) -> Workload {
+        Workload::Chains {
+            length: self.air.layout.length,
+        }
+    }
+
+    fn trace_height(&self) -> usize {
+        (S::ROWS * self.num_permutations / S::LANES).next_power_of_two()
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+        (0..self.num_chains())
+            .flat_map(|_| self.air.step.random_digest(&mut rng))
+            .collect()
+    }
+
+    fn generate_trace(&self, input: Self::Input) -> RowMajorMatrix<Val<SC>> {
+        let step = &self.air.step;
+        let layout = &self.air.layout;
+        let digest = S::DIGEST;
+        let num_chains = self.num_chains();
+
+        // Hash the chains position by position, and then move the rows of each chain together.
+        let mut positions = Vec::<RowMajorMatrix<Val<SC>>>::with_capacity(layout.length);
+        let mut digests = input;
+        for _ in 0..layout.length {
+            digests.resize(digest * num_chains.next_power_of_two(), Val::<SC>::ZERO);
+            let trace = step.generate_trace(&digests, 0);
+            digests = (0..num_chains)
+                .flat_map(|idx| {
+                    let row = Self::output_row(&trace, idx / S::LANES);
+                    let output = &step.output()[(idx % S::LANES) * digest..][..digest];
+                    output.iter().map(move |&column| row[column])
+                })
+                .collect();
+            positions.push(trace);
+        }
+
+        let width = layout.width + Layout::NUM_COLUMNS;
+        let height = Plonky3Circuit::<SC>::trace_height(self);
+        let mut values = Vec::with_capacity((height * width) << self.log_blowup);
+        let mut columns = [Val::<SC>::ZERO; Layout::NUM_COLUMNS];
+        for group in 0..layout.groups {
+            for (position, trace) in positions.iter().enumerate() {
+                let remaining = layout.length - 1 - position;
+                columns[Layout::IS_REAL] = Val::<SC>::ONE;
+                columns[Layout::IS_END] = Val::<SC>::from_bool(remaining == 0);
+                columns[Layout::REMAINING] = Val::<SC>::from_usize(remaining);
+                columns[Layout::GROUP] = Val::<SC>::from_usize(group);
+                for row in trace.values[S::ROWS * group * layout.width..]
+                    .chunks(layout.width)
+                    .take(S::ROWS)
+                {
+                    values.extend_from_slice(row);
+                    values.extend_from_slice(&columns);
+                }
+            }
+        }
+
+        // Permutations of the first position repeated, which aren't linked to each other.
+        let padding = height - S::ROWS * layout.groups * layout.length;
+        let rows = positions[0]
+            .values
+            .chunks(layout.width)
+            .take(S::ROWS * layout.groups);
+        for (idx, row) in rows.cycle().take(padding).enumerate() {
+            columns[Layout::IS_REAL] = Val::<SC>::ZERO;
+            columns[Layout::IS_END] = Val::<SC>::ONE;
+            columns[Layout::REMAINING] = Val::<SC>::ZERO;
+            columns[Layout::GROUP] = Val::<SC>::from_usize(layout.groups + idx / S::ROWS);
+            values.extend_from_slice(row);
+            values.extend_from_slice(&columns);
+        }
+
+        RowMajorMatrix::new(values, width)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::circuit::Step;
+use bench::hash::Blake3;
+use core::borrow::Borrow;
+use p3_blake3_air::{Blake3Air, Blake3Cols, NUM_BLAKE3_COLS, generate_trace_rows};
+use p3_field::PrimeField64;
+use p3_matrix::dense::RowMajorMatrix;
+use rand::{Rng, RngCore};
+
+/// Number of words of a digest.
+const DIGEST_WORDS: usize = 8;
+
+/// Number of 16-bit limbs of a word, as in the outputs of [`Blake3Cols`].
+const LIMBS: usize = 2;
+
+const DIGEST: usize = DIGEST_WORDS * LIMBS;
+
+/// Initial chaining value of BLAKE3.
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// Compression of a digest padded by zeros to a block, with the initial chaining value and the
+/// first half of the output as digest, in 16-bit limbs as in [`Blake3Cols`]. The counter, block
+/// length and flags are the ones of `p3_blake3_air::generate_trace_rows`, i.e. the index of the
+/// row, the number of rows and zero.
+pub struct Blake3Step {
+    air: Blake3Air,
+    input: Vec<Vec<(usize, u64)>>,
+    output: Vec<usize>,
+    fixed: Vec<(usize, u64)>,
+    counter: Vec<(usize, u64)>,
+    block_len: Vec<(usize, u64)>,
+}
+
+/// Number of low bits of the counter and block length summed to them, the others being zero so
+/// that the sums are below the order of 31-bit fields.
+const POSITION_BITS: usize = 30;
+
+/// Returns the low bits of `word` with their coefficients summing to it.
+fn low_bits(word: &[usize; 32]) -> Vec<(usize, u64)> {
+    (word[..POSITION_BITS].iter().enumerate())
+        .map(|(idx, &bit)| (bit, 1 << idx))
+        .collect()
+}
+
+/// Returns the input of `digest` in limbs, with the block in the first 16 words and the chaining
+/// value in the last 8.
+fn block_input<F: PrimeField64>(digest: &[F]) -> [u32; 24] {
+    let mut input = [0; 24];
+    for (word, limbs) in input.iter_mut().zip(digest.chunks(LIMBS)) {
+        *word = (limbs.iter().enumerate())
+            .map(|(idx, limb)| (limb.as_canonical_u64() as u32) << (16 * idx))
+            .sum();
+    }
+    input[16..].copy_from_slice(&IV);
+    input
+}
+
+impl<F: PrimeField64> Step<F> for Blake3Step {
+    type Air = Blake3Air;
+    type Hash = Blake3;
+
+    const DIGEST: usize = DIGEST;
+    const LANES: usize = 1;
+    const ROWS: usize = 1;
+
+    fn new() -> Self {
+        let columns = (0..NUM_BLAKE3_COLS).collect::<Vec<_>>();
+        let columns: &Blake3Cols<usize> = columns[..].borrow();
+        // Limbs of the output are sums of 16 bits of the input.
+        let input = (columns.inputs[..DIGEST_WORDS].iter())
+            .flat_map(|bits| bits.chunks(16))
+            .map(|bits| {
+                (bits.iter().enumerate())
+                    .map(|(idx, &bit)| (bit, 1 << idx))
+                    .collect()
+            })
+            .collect();
+        let zeros = (columns.inputs[DIGEST_WORDS..].iter().flatten()).map(|&bit| (bit, 0));
+        let iv = (columns.chaining_values.iter().flatten().zip(IV)).flat_map(|(bits, word)| {
+            (bits.iter().enumerate()).map(move |(idx, &bit)| (bit, u64::from((word >> idx) & 1)))
+        });
+        let high_bits = [&columns.counter_low, &columns.block_len]
+            .into_iter()
+            .flat_map(|word| &word[POSITION_BITS..]);
+        let zero_words = (columns.counter_hi.iter()).chain(&columns.flags);
+        let zero_bits = high_bits.chain(zero_words).map(|&bit| (bit, 0));
+        Self {
+            air: Blake3Air {},
+            input,
+            output: (columns.outputs[..DIGEST_WORDS / 4].iter().flatten())
+                .flatten()
+                .copied()
+                .collect(),
+            fixed: zeros.chain(iv).chain(zero_bits).collect(),
+            counter: low_bits(&columns.counter_low),
+            block_len: low_bits(&columns.block_len),
+        }
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn input(&self) -> &[Vec<(usize, u64)>] {
+        &self.input
+    }
+
+    fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    fn fixed(&self) -> &[(usize, u64)] {
+        &self.fixed
+    }
+
+    fn position(&self) -> Option<(&[(usize, u64)], &[(usize, u64)])> {
+        Some((&self.counter, &self.block_len))
+    }
+
+    fn last_row(&self) -> Option<usize> {
+        None
+    }
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<F> {
+        (0..DIGEST).map(|_| F::from_u16(rng.random())).collect()
+    }
+
+    fn generate_trace(&self, digests: &[F], extra_capacity_bits: usize) -> RowMajorMatrix<F> {
+        let inputs = digests.chunks(DIGEST).map(block_input).collect();
+        generate_trace_rows(inputs, extra_capacity_bits)
+    }
+}
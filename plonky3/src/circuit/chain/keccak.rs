@@ -0,0 +1,93 @@
+use crate::circuit::{
+    Step,
+    merkle::keccak::{DIGEST, DIGEST_LANES, lane, padded_input},
+};
+use bench::hash::KeccakF1600;
+use core::borrow::Borrow;
+use p3_field::{PrimeCharacteristicRing, PrimeField64};
+use p3_keccak_air::{KeccakAir, KeccakCols, NUM_KECCAK_COLS, NUM_ROUNDS, generate_trace_rows};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::{Rng, RngCore};
+
+/// Keccak-256 of a digest, i.e. a single permutation of it with `pad10*1` padding in lanes 4 and
+/// 16, and the first 4 lanes as output, in 16-bit limbs as in [`KeccakCols`].
+pub struct KeccakStep {
+    air: KeccakAir,
+    input: Vec<Vec<(usize, u64)>>,
+    output: Vec<usize>,
+    fixed: Vec<(usize, u64)>,
+    last_row: usize,
+}
+
+impl<F: PrimeField64> Step<F> for KeccakStep {
+    type Air = KeccakAir;
+    type Hash = KeccakF1600;
+
+    const DIGEST: usize = DIGEST;
+    const LANES: usize = 1;
+    const ROWS: usize = NUM_ROUNDS;
+
+    fn new() -> Self {
+        let columns = (0..NUM_KECCAK_COLS).collect::<Vec<_>>();
+        let columns: &KeccakCols<usize> = columns[..].borrow();
+        let preimage = |idx: usize| {
+            let (x, y) = lane(idx);
+            columns.preimage[y][x]
+        };
+        let padding = padded_input(&[F::ZERO; DIGEST]);
+        Self {
+            air: KeccakAir {},
+            input: (0..DIGEST_LANES)
+                .flat_map(preimage)
+                .map(|column| vec![(column, 1)])
+                .collect(),
+            output: (0..DIGEST_LANES)
+                .flat_map(|idx| match lane(idx) {
+                    (0, 0) => columns.a_prime_prime_prime_0_0_limbs,
+                    (x, y) => columns.a_prime_prime[y][x],
+                })
+                .collect(),
+            fixed: (DIGEST_LANES..25)
+                .flat_map(|idx| {
+                    let value = padding[idx];
+                    (preimage(idx).into_iter().enumerate())
+                        .map(move |(limb, column)| (column, (value >> (16 * limb)) & 0xffff))
+                })
+                .collect(),
+            last_row: columns.step_flags[NUM_ROUNDS - 1],
+        }
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn input(&self) -> &[Vec<(usize, u64)>] {
+        &self.input
+    }
+
+    fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    fn fixed(&self) -> &[(usize, u64)] {
+        &self.fixed
+    }
+
+    fn position(&self) -> Option<(&[(usize, u64)], &[(usize, u64)])> {
+        None
+    }
+
+    fn last_row(&self) -> Option<usize> {
+        Some(self.last_row)
+    }
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<F> {
+        (0..DIGEST).map(|_| F::from_u16(rng.random())).collect()
+    }
+
+    fn generate_trace(&self, digests: &[F], extra_capacity_bits: usize) -> RowMajorMatrix<F> {
+        let inputs = digests.chunks(DIGEST).map(padded_input).collect();
+        generate_trace_rows(inputs, extra_capacity_bits)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::circuit::{
+    Step,
+    koala_bear_poseidon2::{
+        HALF_FULL_ROUNDS, KoalaBearPoseidon2Air, PARTIAL_ROUNDS, SBOX_DEGREE, SBOX_REGISTERS,
+        VECTOR_LEN, WIDTH, random_element,
+    },
+};
+use bench::hash::KoalaBearPoseidon2;
+use core::borrow::Borrow;
+use p3_air::BaseAir;
+use p3_field::PrimeCharacteristicRing;
+use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_poseidon2_air::{
+    Poseidon2Cols, RoundConstants, VectorizedPoseidon2Air, generate_vectorized_trace_rows,
+};
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+
+/// Number of elements of a digest, half of the state.
+const DIGEST: usize = WIDTH / 2;
+
+/// Truncated permutation of a digest padded by zeros, keeping the first half of the output as in
+/// `p3_symmetric::TruncatedPermutation`, with permutations of [`VECTOR_LEN`] chains in a row as
+/// in [`KoalaBearPoseidon2Circuit`](crate::circuit::KoalaBearPoseidon2Circuit).
+pub struct KoalaBearPoseidon2Step {
+    constants: RoundConstants<KoalaBear, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+    air: KoalaBearPoseidon2Air,
+    input: Vec<Vec<(usize, u64)>>,
+    output: Vec<usize>,
+    fixed: Vec<(usize, u64)>,
+}
+
+impl Step<KoalaBear> for KoalaBearPoseidon2Step {
+    type Air = KoalaBearPoseidon2Air;
+    type Hash = KoalaBearPoseidon2;
+
+    const DIGEST: usize = DIGEST;
+    const LANES: usize = VECTOR_LEN;
+    const ROWS: usize = 1;
+
+    fn new() -> Self {
+        let rng = StdRng::from_os_rng();
+        let constants = RoundConstants::from_rng(&mut rng.clone());
+        let air = VectorizedPoseidon2Air::new(RoundConstants::from_rng(&mut rng.clone()));
+        let width = BaseAir::<KoalaBear>::width(&air) / VECTOR_LEN;
+        let columns = (0..width).collect::<Vec<_>>();
+        let columns: &Poseidon2Cols<
+            usize,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = columns[..].borrow();
+        let lanes = (0..VECTOR_LEN).map(|lane| lane * width);
+        Self {
+            constants,
+            air,
+            input: lanes
+                .clone()
+                .flat_map(|offset| columns.inputs[..DIGEST].iter().map(move |&c| offset + c))
+                .map(|column| vec![(column, 1)])
+                .collect(),
+            output: lanes
+                .clone()
+                .flat_map(|offset| {
+                    let post = &columns.ending_full_rounds[HALF_FULL_ROUNDS - 1].post;
+                    post[..DIGEST].iter().map(move |&c| offset + c)
+                })
+                .collect(),
+            fixed: lanes
+                .flat_map(|offset| columns.inputs[DIGEST..].iter().map(move |&c| offset + c))
+                .map(|column| (column, 0))
+                .collect(),
+        }
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn input(&self) -> &[Vec<(usize, u64)>] {
+        &self.input
+    }
+
+    fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    fn fixed(&self) -> &[(usize, u64)] {
+        &self.fixed
+    }
+
+    fn position(&self) -> Option<(&[(usize, u64)], &[(usize, u64)])> {
+        None
+    }
+
+    fn last_row(&self) -> Option<usize> {
+        None
+    }
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<KoalaBear> {
+        (0..DIGEST).map(|_| random_element(rng)).collect()
+    }
+
+    fn generate_trace(
+        &self,
+        digests: &[KoalaBear],
+        extra_capacity_bits: usize,
+    ) -> RowMajorMatrix<KoalaBear> {
+        let inputs = digests
+            .chunks(DIGEST)
+            .map(|digest| {
+                let mut input = [KoalaBear::ZERO; WIDTH];
+                input[..DIGEST].copy_from_slice(digest);
+                input
+            })
+            .collect();
+        generate_vectorized_trace_rows::<
+            KoalaBear,
+            GenericPoseidon2LinearLayersKoalaBear,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            VECTOR_LEN,
+        >(inputs, &self.constants, extra_capacity_bits)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Plonky3Circuit;
+use bench::hash::KeccakF1600;
 use p3_field::PrimeField64;
 use p3_keccak_air::{KeccakAir, generate_trace_rows};
 use p3_matrix::dense::RowMajorMatrix;
@@ -16,6 +17,7 @@ where
     Val<SC>: PrimeField64,
 {
     type Air = KeccakAir;
+    type Hash = KeccakF1600;
     type Input = Vec<[u64; 25]>;
 
     fn new(num_permutations: usize, log_blowup: usize) -> Self
@@ -1,20 +1,28 @@
 use crate::Plonky3Circuit;
+use bench::hash::KoalaBearPoseidon2;
 use p3_commit::PolynomialSpace;
+use p3_field::PrimeCharacteristicRing;
 use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_poseidon2_air::{RoundConstants, VectorizedPoseidon2Air, generate_vectorized_trace_rows};
 use p3_uni_stark::{Domain, StarkGenericConfig, Val};
-use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+use rand::{RngCore, SeedableRng, rngs::StdRng};
 
 // Copied from https://github.com/Plonky3/Plonky3/blob/abdc2a0/poseidon2-air/examples/prove_poseidon2_koala_bear_keccak.rs#L26-L34.
-const WIDTH: usize = 16;
-const SBOX_DEGREE: u64 = 3;
-const SBOX_REGISTERS: usize = 0;
-const HALF_FULL_ROUNDS: usize = 4;
-const PARTIAL_ROUNDS: usize = 20;
-const VECTOR_LEN: usize = 1 << 3;
+pub(super) const WIDTH: usize = 16;
+pub(super) const SBOX_DEGREE: u64 = 3;
+pub(super) const SBOX_REGISTERS: usize = 0;
+pub(super) const HALF_FULL_ROUNDS: usize = 4;
+pub(super) const PARTIAL_ROUNDS: usize = 20;
+pub(super) const VECTOR_LEN: usize = 1 << 3;
 
-type KoalaBearPoseidon2Air = VectorizedPoseidon2Air<
+/// Returns a word of `rng` reduced modulo the prime, which unlike the rejection sampling of
+/// `Rng::random` terminates on any bytes of an input source.
+pub(super) fn random_element(rng: &mut impl RngCore) -> KoalaBear {
+    KoalaBear::from_u32(rng.next_u32())
+}
+
+pub(super) type KoalaBearPoseidon2Air = VectorizedPoseidon2Air<
     KoalaBear,
     GenericPoseidon2LinearLayersKoalaBear,
     WIDTH,
@@ -37,6 +45,7 @@ where
     Domain<SC>: PolynomialSpace<Val = KoalaBear>,
 {
     type Air = KoalaBearPoseidon2Air;
+    type Hash = KoalaBearPoseidon2;
     type Input = Vec<[KoalaBear; WIDTH]>;
 
     fn new(num_permutations: usize, log_blowup: usize) -> Self
@@ -68,7 +77,7 @@ where
 
     fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
         (0..Plonky3Circuit::<SC>::num_permutations(self))
-            .map(|_| rng.random())
+            .map(|_| core::array::from_fn(|_| random_element(&mut rng)))
             .collect()
     }
 
@@ -0,0 +1,427 @@
+use crate::{
+    Plonky3Circuit,
+    circuit::{Plonky3Air, Prefix},
+};
+use bench::{hash::HashDescriptor, workload::Workload};
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_matrix::{Matrix, dense::RowMajorMatrix};
+use p3_uni_stark::{StarkGenericConfig, Val};
+use rand::RngCore;
+
+pub(super) mod keccak;
+mod koala_bear_poseidon2;
+
+pub use keccak::KeccakCompression;
+pub use koala_bear_poseidon2::KoalaBearPoseidon2Compression;
+
+/// Permutation compressing a left and a right digest into one, proven by the AIR of the
+/// permutation with its columns first in rows of [`MerkleAir`].
+pub trait Compression<F: Field>: Sync {
+    type Air: BaseAir<F> + Sync;
+    type Hash: HashDescriptor;
+
+    /// Number of field elements of a digest.
+    const DIGEST: usize;
+
+    /// Number of rows of a permutation.
+    const ROWS: usize;
+
+    fn new() -> Self;
+
+    fn air(&self) -> &Self::Air;
+
+    /// Returns the columns of the left and the right digest of the input, which are constant over
+    /// the rows of a permutation.
+    fn input(&self) -> (&[usize], &[usize]);
+
+    /// Returns the columns of the output digest in the last row of a permutation.
+    fn output(&self) -> &[usize];
+
+    /// Returns the input columns fixed to constants, e.g. padding of a sponge.
+    fn fixed(&self) -> &[(usize, u64)];
+
+    /// Returns the column being one in the last row of a permutation, if it takes several rows.
+    fn last_row(&self) -> Option<usize>;
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<F>;
+
+    /// Returns the trace of compressing each of `inputs`, concatenations of a left and a right
+    /// digest numbering a power of two, in order from the first row.
+    fn generate_trace(&self, inputs: &[F], extra_capacity_bits: usize) -> RowMajorMatrix<F>;
+}
+
+/// Columns of [`MerkleAir`] after the columns of the permutation, relative to them. They are
+/// constant over the rows of a permutation.
+struct Layout {
+    /// Number of columns of the permutation.
+    width: usize,
+    /// Height of the tree.
+    depth: usize,
+    digest: usize,
+}
+
+impl Layout {
+    /// Whether the permutation compresses two leaves.
+    const IS_LEAF: usize = 0;
+    /// Whether the permutation compresses two nodes, the previous permutation and the top of
+    /// the stack.
+    const IS_NODE: usize = 1;
+    /// Whether the permutation computes the root.
+    const IS_ROOT: usize = 2;
+    /// Height of the node computed by the permutation, one for leaves.
+    const HEIGHT: usize = 3;
+    /// Stack of left children waiting for their right sibling, not including the node computed
+    /// by the permutation, each of a digest and its height, zero if empty.
+    const STACK: usize = 4;
+
+    fn column(&self, column: usize) -> usize {
+        self.width + column
+    }
+
+    /// Returns the column of the digest of `slot` of the stack, counted from the top.
+    fn stack(&self, slot: usize) -> usize {
+        Self::STACK + slot * (self.digest + 1)
+    }
+
+    fn stack_height(&self, slot: usize) -> usize {
+        self.stack(slot) + self.digest
+    }
+
+    fn num_columns(&self) -> usize {
+        self.stack(self.depth)
+    }
+}
+
+/// AIR computing the root of a binary Merkle tree by compressions of `C` in post-order, so the
+/// right child of a node is the previous permutation, and the left child is kept on a stack of at
+/// most `depth` nodes. The heights of both children are constrained to be equal, so the tree is
+/// perfect, and the root is a public output.
+pub struct MerkleAir<C> {
+    compression: C,
+    layout: Layout,
+}
+
+impl<C> MerkleAir<C> {
+    pub fn new<F: Field>(compression: C, depth: usize) -> Self
+    where
+        C: Compression<F>,
+    {
+        let layout = Layout {
+            width: BaseAir::<F>::width(compression.air()),
+            depth,
+            digest: C::DIGEST,
+        };
+        Self {
+            compression,
+            layout,
+        }
+    }
+}
+
+impl<F: Field, C: Compression<F>> BaseAir<F> for MerkleAir<C> {
+    fn width(&self) -> usize {
+        self.layout.width + self.layout.num_columns()
+    }
+}
+
+impl<AB, C> Air<AB> for MerkleAir<C>
+where
+    AB: AirBuilderWithPublicValues,
+    C: Compression<AB::F>,
+    C::Air: for<'a> Air<Prefix<'a, AB>>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let layout = &self.layout;
+        let (left, right) = self.compression.input();
+        let output = self.compression.output();
+        self.compression.air().eval(&mut Prefix {
+            builder,
+            width: layout.width,
+        });
+
+        let main = builder.main();
+        let local_row = main.row_slice(0).expect("The matrix is empty?");
+        let next_row = main.row_slice(1).expect("The matrix only has 1 row?");
+        let public_values = builder
+            .public_values()
+            .iter()
+            .map(|&value| value.into())
+            .collect::<Vec<AB::Expr>>();
+        let local = |column: usize| -> AB::Expr { local_row[layout.column(column)].into() };
+        let next = |column: usize| -> AB::Expr { next_row[layout.column(column)].into() };
+        let local_inner = |column: usize| -> AB::Expr { local_row[column].into() };
+        let next_inner = |column: usize| -> AB::Expr { next_row[column].into() };
+
+        let is_real = local(Layout::IS_LEAF) + local(Layout::IS_NODE);
+        let next_is_real = next(Layout::IS_LEAF) + next(Layout::IS_NODE);
+        builder.assert_bool(local(Layout::IS_LEAF));
+        builder.assert_bool(local(Layout::IS_NODE));
+        builder.assert_bool(is_real.clone());
+        builder.assert_bool(local(Layout::IS_ROOT));
+        builder
+            .when(local(Layout::IS_LEAF))
+            .assert_one(local(Layout::HEIGHT));
+        for &(column, value) in self.compression.fixed() {
+            builder
+                .when(is_real.clone())
+                .assert_eq(local_inner(column), AB::Expr::from_u64(value));
+        }
+
+        // The tree starts from a leaf with an empty stack, and the last row is padding, so the
+        // root is followed by a transition.
+        let mut first_row = builder.when_first_row();
+        first_row.assert_one(local(Layout::IS_LEAF));
+        for column in Layout::STACK..layout.num_columns() {
+            first_row.assert_zero(local(column));
+        }
+        builder.when_last_row().assert_zero(is_real.clone());
+
+        let boundary = match self.compression.last_row() {
+            Some(column) => {
+                let mut within = builder.when_transition();
+                let mut within = within.when(AB::Expr::ONE - local_inner(column));
+                for column in 0..layout.num_columns() {
+                    within.assert_eq(next(column), local(column));
+                }
+                local_inner(column)
+            }
+            None => AB::Expr::ONE,
+        };
+
+        let mut transition = builder.when_transition();
+        let mut transition = transition.when(boundary.clone());
+        transition.assert_zero(next_is_real.clone() * (AB::Expr::ONE - is_real.clone()));
+        transition.assert_eq(is_real - next_is_real, local(Layout::IS_ROOT));
+
+        // A leaf pushes the previous node onto the stack.
+        let mut push = transition.when(next(Layout::IS_LEAF));
+        push.assert_zero(local(layout.stack_height(layout.depth - 1)));
+        for (idx, &column) in output.iter().enumerate() {
+            push.assert_eq(next(layout.stack(0) + idx), local_inner(column));
+        }
+        push.assert_eq(next(layout.stack_height(0)), local(Layout::HEIGHT));
+        for column in layout.stack(1)..layout.num_columns() {
+            push.assert_eq(next(column), local(column - layout.digest - 1));
+        }
+
+        // A node pops its left child from the stack, which is of the height of its right child.
+        let mut pop = transition.when(next(Layout::IS_NODE));
+        pop.assert_eq(local(layout.stack_height(0)), local(Layout::HEIGHT));
+        pop.assert_eq(next(Layout::HEIGHT), local(Layout::HEIGHT) + AB::Expr::ONE);
+        for (idx, &column) in left.iter().enumerate() {
+            pop.assert_eq(next_inner(column), local(layout.stack(0) + idx));
+        }
+        for (&column, &output) in right.iter().zip(output) {
+            pop.assert_eq(next_inner(column), local_inner(output));
+        }
+        for column in Layout::STACK..layout.stack(layout.depth - 1) {
+            pop.assert_eq(next(column), local(column + layout.digest + 1));
+        }
+        for column in layout.stack(layout.depth - 1)..layout.num_columns() {
+            pop.assert_zero(next(column));
+        }
+
+        // The root is of the height of the tree with an empty stack.
+        let mut root = builder.when(local(Layout::IS_ROOT) * boundary);
+        root.assert_eq(local(Layout::HEIGHT), AB::Expr::from_usize(layout.depth));
+        for slot in 0..layout.depth {
+            root.assert_zero(local(layout.stack_height(slot)));
+        }
+        for (&column, value) in output.iter().zip(public_values) {
+            root.assert_eq(local_inner(column), value);
+        }
+    }
+}
+
+/// Circuit proving the root of a Merkle tree over as many leaves as permutations it's set up
+/// with, a power of two, by one less compressions.
+pub struct MerkleCircuit<C> {
+    air: MerkleAir<C>,
+    num_leaves: usize,
+    log_blowup: usize,
+}
+
+impl<C> MerkleCircuit<C> {
+    /// Returns the row of the output of the `idx`-th permutation in `trace`.
+    fn output_row<F: Field>(trace: &RowMajorMatrix<F>, idx: usize) -> &[F]
+    where
+        C: Compression<F>,
+    {
+        let width = trace.width();
+        &trace.values[(C::ROWS * (idx + 1) - 1) * width..][..width]
+    }
+}
+
+/// Returns the nodes of the subtree of height `height` at `idx` in post-order, as their height and
+/// index within their level.
+fn post_order(height: usize, idx: usize, nodes: &mut Vec<(usize, usize)>) {
+    if height > 1 {
+        post_order(height - 1, 2 * idx, nodes);
+        post_order(height - 1, 2 * idx + 1, nodes);
+    }
+    nodes.push((height, idx));
+}
+
+impl<SC, C> Plonky3Circuit<SC> for MerkleCircuit<C>
+where
+    SC: StarkGenericConfig,
+    C: Compression<Val<SC>>,
+    MerkleAir<C>: Plonky3Air<SC>,
+{
+    type Air = MerkleAir<C>;
+    type Hash = C::Hash;
+    /// Leaf digests.
+    type Input = Vec<Val<SC>>;
+
+    const HAS_PUBLIC_VALUES: bool = true;
+
+    fn new(num_permutations: usize, log_blowup: usize) -> Self
+    where
+        Self: Sized,
+    {
+        assert!(
+            num_permutations >= 2 && num_permutations.is_power_of_two(),
+            "number of leaves must be a power of two of at least 2"
+        );
+        Self {
+            air: MerkleAir::new::<Val<SC>>(C::new(), num_permutations.ilog2() as usize),
+            num_leaves: num_permutations,
+            log_blowup,
+        }
+    }
+
+    fn num_permutations(&self) -> usize {
+        self.num_leaves - 1
+    }
+
+    fn workload(&self) -> Workload {
+        Workload::MerkleTree
+    }
+
+    fn trace_height(&self) -> usize {
+        (C::ROWS * (self.num_leaves - 1) + 1).next_power_of_two()
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn generate_input(&self, mut rng: impl RngCore) -> Self::Input {
+        (0..self.num_leaves)
+            .flat_map(|_| self.air.compression.random_digest(&mut rng))
+            .collect()
+    }
+
+    fn generate_trace(&self, input: Self::Input) -> RowMajorMatrix<Val<SC>> {
+        let compression = &self.air.compression;
+        let layout = &self.air.layout;
+        let digest = C::DIGEST;
+
+        // Compress level by level, and then move the rows of each permutation into post-order.
+        let mut levels = Vec::<RowMajorMatrix<Val<SC>>>::with_capacity(layout.depth);
+        let mut outputs = Vec::<Vec<Val<SC>>>::with_capacity(layout.depth);
+        for _ in 0..layout.depth {
+            let inputs = outputs.last().unwrap_or(&input);
+            let trace = compression.generate_trace(inputs, 0);
+            let digests = (0..inputs.len() / (2 * digest))
+                .flat_map(|idx| {
+                    let row = Self::output_row(&trace, idx);
+                    compression.output().iter().map(move |&column| row[column])
+                })
+                .collect();
+            outputs.push(digests);
+            levels.push(trace);
+        }
+
+        let mut nodes = Vec::with_capacity(self.num_leaves - 1);
+        post_order(layout.depth, 0, &mut nodes);
+
+        let width = layout.width + layout.num_columns();
+        let height = Plonky3Circuit::<SC>::trace_height(self);
+        let mut values = Vec::with_capacity((height * width) << self.log_blowup);
+        let mut columns = vec![Val::<SC>::ZERO; layout.num_columns()];
+        let mut stack = Vec::<(usize, usize)>::with_capacity(layout.depth);
+        let mut previous = None;
+        for &(node_height, idx) in &nodes {
+            if node_height == 1 {
+                stack.extend(previous);
+            } else {
+                stack.pop();
+            }
+            previous = Some((node_height, idx));
+
+            columns.fill(Val::<SC>::ZERO);
+            columns[Layout::IS_LEAF] = Val::<SC>::from_bool(node_height == 1);
+            columns[Layout::IS_NODE] = Val::<SC>::from_bool(node_height > 1);
+            columns[Layout::IS_ROOT] = Val::<SC>::from_bool(node_height == layout.depth);
+            columns[Layout::HEIGHT] = Val::<SC>::from_usize(node_height);
+            for (slot, &(child_height, child)) in stack.iter().rev().enumerate() {
+                columns[layout.stack(slot)..layout.stack_height(slot)]
+                    .copy_from_slice(&outputs[child_height - 1][child * digest..][..digest]);
+                columns[layout.stack_height(slot)] = Val::<SC>::from_usize(child_height);
+            }
+
+            let level = &levels[node_height - 1];
+            for row in level.values[C::ROWS * idx * layout.width..]
+                .chunks(layout.width)
+                .take(C::ROWS)
+            {
+                values.extend_from_slice(row);
+                values.extend_from_slice(&columns);
+            }
+        }
+
+        // Permutations of zero inputs, which aren't constrained by the tree.
+        let padding = height - C::ROWS * nodes.len();
+        let inputs =
+            vec![Val::<SC>::ZERO; 2 * digest * padding.div_ceil(C::ROWS).next_power_of_two()];
+        columns.fill(Val::<SC>::ZERO);
+        for row in compression
+            .generate_trace(&inputs, 0)
+            .values
+            .chunks(layout.width)
+            .take(padding)
+        {
+            values.extend_from_slice(row);
+            values.extend_from_slice(&columns);
+        }
+
+        RowMajorMatrix::new(values, width)
+    }
+
+    fn public_values(&self, trace: &RowMajorMatrix<Val<SC>>) -> Vec<Val<SC>> {
+        let row = Self::output_row(trace, self.num_leaves - 2);
+        let compression = &self.air.compression;
+        compression
+            .output()
+            .iter()
+            .map(|&column| row[column])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Plonky3,
+        circuit::{KoalaBearPoseidon2Compression, MerkleCircuit},
+        config::KoalaBearKeccakMtConfig,
+    };
+    use bench::{HashInSnark, input::InputSource};
+    use p3_field::PrimeCharacteristicRing;
+    use p3_koala_bear::KoalaBear;
+
+    type Poseidon2Merkle =
+        Plonky3<KoalaBearKeccakMtConfig, MerkleCircuit<KoalaBearPoseidon2Compression>>;
+
+    #[test]
+    fn tampered_root() {
+        let snark = Poseidon2Merkle::new(1 << 4);
+        let mut proof = snark.prove(snark.generate_input(InputSource::new()));
+        snark.verify(&proof).unwrap();
+        let root = &mut proof.1;
+        root[0] += KoalaBear::ONE;
+        assert!(snark.verify(&proof).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::circuit::Compression;
+use bench::hash::KeccakF1600;
+use core::borrow::Borrow;
+use p3_field::{PrimeCharacteristicRing, PrimeField64};
+use p3_keccak_air::{KeccakAir, KeccakCols, NUM_KECCAK_COLS, NUM_ROUNDS, generate_trace_rows};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::{Rng, RngCore};
+
+/// Number of 16-bit limbs of a lane.
+const LIMBS: usize = 4;
+
+/// Number of lanes of a digest.
+pub(in crate::circuit) const DIGEST_LANES: usize = 4;
+
+pub(in crate::circuit) const DIGEST: usize = DIGEST_LANES * LIMBS;
+
+/// Keccak-256 of the concatenation of two digests, i.e. a single permutation of both with
+/// `pad10*1` padding in lanes 8 and 16, and the first 4 lanes as output, in 16-bit limbs as in
+/// [`KeccakCols`].
+pub struct KeccakCompression {
+    air: KeccakAir,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    output: Vec<usize>,
+    fixed: Vec<(usize, u64)>,
+    last_row: usize,
+}
+
+/// Returns the lane of `idx` in the state, as coordinates `x` and `y` with index `x + 5 * y`.
+pub(in crate::circuit) fn lane(idx: usize) -> (usize, usize) {
+    (idx % 5, idx / 5)
+}
+
+/// Returns the input of `message` in limbs, e.g. a left and a right digest, padded by Keccak-256.
+pub(in crate::circuit) fn padded_input<F: PrimeField64>(message: &[F]) -> [u64; 25] {
+    let mut state = [0; 25];
+    for (lane, limbs) in state.iter_mut().zip(message.chunks(LIMBS)) {
+        *lane = (limbs.iter().enumerate())
+            .map(|(idx, limb)| limb.as_canonical_u64() << (16 * idx))
+            .sum();
+    }
+    state[message.len() / LIMBS] = 1;
+    state[16] = 1 << 63;
+    state
+}
+
+impl<F: PrimeField64> Compression<F> for KeccakCompression {
+    type Air = KeccakAir;
+    type Hash = KeccakF1600;
+
+    const DIGEST: usize = DIGEST;
+    const ROWS: usize = NUM_ROUNDS;
+
+    fn new() -> Self {
+        let columns = (0..NUM_KECCAK_COLS).collect::<Vec<_>>();
+        let columns: &KeccakCols<usize> = columns[..].borrow();
+        let preimage = |idx: usize| {
+            let (x, y) = lane(idx);
+            columns.preimage[y][x]
+        };
+        let padding = padded_input(&[F::ZERO; 2 * DIGEST]);
+        Self {
+            air: KeccakAir {},
+            left: (0..DIGEST_LANES).flat_map(preimage).collect(),
+            right: (DIGEST_LANES..2 * DIGEST_LANES)
+                .flat_map(preimage)
+                .collect(),
+            output: (0..DIGEST_LANES)
+                .flat_map(|idx| match lane(idx) {
+                    (0, 0) => columns.a_prime_prime_prime_0_0_limbs,
+                    (x, y) => columns.a_prime_prime[y][x],
+                })
+                .collect(),
+            fixed: (2 * DIGEST_LANES..25)
+                .flat_map(|idx| {
+                    let value = padding[idx];
+                    (preimage(idx).into_iter().enumerate())
+                        .map(move |(limb, column)| (column, (value >> (16 * limb)) & 0xffff))
+                })
+                .collect(),
+            last_row: columns.step_flags[NUM_ROUNDS - 1],
+        }
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn input(&self) -> (&[usize], &[usize]) {
+        (&self.left, &self.right)
+    }
+
+    fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    fn fixed(&self) -> &[(usize, u64)] {
+        &self.fixed
+    }
+
+    fn last_row(&self) -> Option<usize> {
+        Some(self.last_row)
+    }
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<F> {
+        (0..DIGEST).map(|_| F::from_u16(rng.random())).collect()
+    }
+
+    fn generate_trace(&self, inputs: &[F], extra_capacity_bits: usize) -> RowMajorMatrix<F> {
+        let inputs = inputs.chunks(2 * DIGEST).map(padded_input).collect();
+        generate_trace_rows(inputs, extra_capacity_bits)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::circuit::{
+    Compression,
+    koala_bear_poseidon2::{
+        HALF_FULL_ROUNDS, PARTIAL_ROUNDS, SBOX_DEGREE, SBOX_REGISTERS, WIDTH, random_element,
+    },
+};
+use bench::hash::KoalaBearPoseidon2;
+use core::borrow::Borrow;
+use p3_air::BaseAir;
+use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_poseidon2_air::{
+    Poseidon2Cols, RoundConstants, VectorizedPoseidon2Air, generate_vectorized_trace_rows,
+};
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+
+/// Number of elements of a digest, half of the state.
+const DIGEST: usize = WIDTH / 2;
+
+/// Single permutation per row, so the input and output of a compression are in the same row.
+const VECTOR_LEN: usize = 1;
+
+type KoalaBearPoseidon2Air = VectorizedPoseidon2Air<
+    KoalaBear,
+    GenericPoseidon2LinearLayersKoalaBear,
+    WIDTH,
+    SBOX_DEGREE,
+    SBOX_REGISTERS,
+    HALF_FULL_ROUNDS,
+    PARTIAL_ROUNDS,
+    VECTOR_LEN,
+>;
+
+/// Truncated permutation of the concatenation of two digests, keeping the first half of the
+/// output as in `p3_symmetric::TruncatedPermutation`.
+pub struct KoalaBearPoseidon2Compression {
+    constants: RoundConstants<KoalaBear, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+    air: KoalaBearPoseidon2Air,
+    input: Vec<usize>,
+    output: Vec<usize>,
+}
+
+impl Compression<KoalaBear> for KoalaBearPoseidon2Compression {
+    type Air = KoalaBearPoseidon2Air;
+    type Hash = KoalaBearPoseidon2;
+
+    const DIGEST: usize = DIGEST;
+    const ROWS: usize = 1;
+
+    fn new() -> Self {
+        let rng = StdRng::from_os_rng();
+        let constants = RoundConstants::from_rng(&mut rng.clone());
+        let air = VectorizedPoseidon2Air::new(RoundConstants::from_rng(&mut rng.clone()));
+        let columns = (0..BaseAir::<KoalaBear>::width(&air)).collect::<Vec<_>>();
+        let columns: &Poseidon2Cols<
+            usize,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = columns[..].borrow();
+        Self {
+            constants,
+            air,
+            input: columns.inputs.to_vec(),
+            output: columns.ending_full_rounds[HALF_FULL_ROUNDS - 1].post[..DIGEST].to_vec(),
+        }
+    }
+
+    fn air(&self) -> &Self::Air {
+        &self.air
+    }
+
+    fn input(&self) -> (&[usize], &[usize]) {
+        self.input.split_at(DIGEST)
+    }
+
+    fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    fn fixed(&self) -> &[(usize, u64)] {
+        &[]
+    }
+
+    fn last_row(&self) -> Option<usize> {
+        None
+    }
+
+    fn random_digest(&self, rng: &mut impl RngCore) -> Vec<KoalaBear> {
+        (0..DIGEST).map(|_| random_element(rng)).collect()
+    }
+
+    fn generate_trace(
+        &self,
+        inputs: &[KoalaBear],
+        extra_capacity_bits: usize,
+    ) -> RowMajorMatrix<KoalaBear> {
+        let inputs = inputs
+            .chunks(WIDTH)
+            .map(|input| input.try_into().unwrap())
+            .collect();
+        generate_vectorized_trace_rows::<
+            KoalaBear,
+            GenericPoseidon2LinearLayersKoalaBear,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            VECTOR_LEN,
+        >(inputs, &self.constants, extra_capacity_bits)
+    }
+}
@@ -1,3 +1,4 @@
+use bench::{report::Description, util::PcsParams};
 use p3_baby_bear::BabyBear;
 use p3_field::extension::BinomialExtensionField;
 use p3_koala_bear::KoalaBear;
@@ -12,10 +13,36 @@ pub type KoalaBearKeccakMtConfig =
 
 pub trait Plonky3Config {
     type StarkGenericConfig: StarkGenericConfig;
+    /// Config of verifiers, counting hashes for [`bench::cost`] unlike the one of provers, with
+    /// proofs serialized alike.
+    type VerifierConfig: StarkGenericConfig;
 
-    fn new(trace_height: usize, log_blowup: usize) -> Self
+    fn new(trace_height: usize, pcs: &PcsParams) -> Self
     where
         Self: Sized;
 
     fn stark_config(&self) -> &Self::StarkGenericConfig;
+
+    fn verifier_config(&self) -> &Self::VerifierConfig;
+
+    fn describe(&self) -> &Description;
+}
+
+/// Name of a field and its degree over the base field in [`Description`]s.
+pub trait FieldName {
+    const NAME: &str;
+    const DEGREE: usize = 1;
+}
+
+impl FieldName for BabyBear {
+    const NAME: &str = "BabyBear";
+}
+
+impl FieldName for KoalaBear {
+    const NAME: &str = "KoalaBear";
+}
+
+impl<F: FieldName, const D: usize> FieldName for BinomialExtensionField<F, D> {
+    const NAME: &str = F::NAME;
+    const DEGREE: usize = D * F::DEGREE;
 }
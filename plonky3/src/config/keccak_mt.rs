@@ -1,4 +1,5 @@
-use crate::config::Plonky3Config;
+use crate::config::{FieldName, Plonky3Config};
+use bench::{cost, hash::KeccakF1600, report::Description, util::PcsParams};
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
@@ -6,56 +7,237 @@ use p3_field::{ExtensionField, PrimeField32, TwoAdicField};
 use p3_fri::{FriParameters, TwoAdicFriPcs};
 use p3_keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
 use p3_merkle_tree::MerkleTreeMmcs;
-use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher};
+use p3_symmetric::{
+    CompressionFunctionFromHasher, CryptographicHasher, CryptographicPermutation,
+    PaddingFreeSponge, Permutation, PseudoCompressionFunction, SerializingHasher,
+};
 use p3_uni_stark::StarkConfig;
 
-pub type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+pub type U64Hash<P = KeccakF> = PaddingFreeSponge<P, 25, 17, 4>;
 pub type FieldHash = SerializingHasher<U64Hash>;
 pub type Compress = CompressionFunctionFromHasher<U64Hash, 2, 4>;
-pub type ValMmcs<F> = MerkleTreeMmcs<[F; VECTOR_LEN], [u64; VECTOR_LEN], FieldHash, Compress, 4>;
-pub type ChallengeMmcs<F, E> = ExtensionMmcs<F, E, ValMmcs<F>>;
 pub type ByteHash = Keccak256Hash;
-pub type Challenger<Val> = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type CountedFieldHash = CountedHasher<SerializingHasher<U64Hash<CountedKeccakF>>>;
+pub type CountedCompress =
+    CountedHasher<CompressionFunctionFromHasher<U64Hash<CountedKeccakF>, 2, 4>>;
+pub type CountedByteHash = CountedHasher<Keccak256Hash>;
+pub type ValMmcs<F, H = FieldHash, C = Compress> =
+    MerkleTreeMmcs<[F; VECTOR_LEN], [u64; VECTOR_LEN], H, C, 4>;
+pub type ChallengeMmcs<F, E, H = FieldHash, C = Compress> = ExtensionMmcs<F, E, ValMmcs<F, H, C>>;
+pub type Challenger<Val, B = ByteHash> = SerializingChallenger32<Val, HashChallenger<u8, B, 32>>;
 pub type Dft<Val> = Radix2DitParallel<Val>;
-pub type Pcs<Val, Challenge> =
-    TwoAdicFriPcs<Val, Dft<Val>, ValMmcs<Val>, ChallengeMmcs<Val, Challenge>>;
+pub type Pcs<Val, Challenge, H = FieldHash, C = Compress> =
+    TwoAdicFriPcs<Val, Dft<Val>, ValMmcs<Val, H, C>, ChallengeMmcs<Val, Challenge, H, C>>;
+pub type Config<Val, Challenge> = StarkConfig<Pcs<Val, Challenge>, Challenge, Challenger<Val>>;
+pub type VerifierConfig<Val, Challenge> = StarkConfig<
+    Pcs<Val, Challenge, CountedFieldHash, CountedCompress>,
+    Challenge,
+    Challenger<Val, CountedByteHash>,
+>;
 
 pub struct KeccakMtConfig<Val, Challenge> {
-    stark_config: StarkConfig<Pcs<Val, Challenge>, Challenge, Challenger<Val>>,
+    stark_config: Config<Val, Challenge>,
+    verifier_config: VerifierConfig<Val, Challenge>,
+    description: Description,
 }
 
-impl<Val: TwoAdicField + PrimeField32, Challenge: TwoAdicField + ExtensionField<Val>> Plonky3Config
-    for KeccakMtConfig<Val, Challenge>
+impl<Val: TwoAdicField + PrimeField32, Challenge: TwoAdicField + ExtensionField<Val>>
+    KeccakMtConfig<Val, Challenge>
 {
-    type StarkGenericConfig = StarkConfig<Pcs<Val, Challenge>, Challenge, Challenger<Val>>;
+    /// Returns the STARK config of `fri_config` with hashers of Merkle trees `field_hash` and
+    /// `compress`, and hasher of the transcript `byte_hash`.
+    fn stark_config_of<H: Clone, C: Clone, B: CryptographicHasher<u8, [u8; 32]>>(
+        fri_config: &FriParameters<()>,
+        field_hash: H,
+        compress: C,
+        byte_hash: B,
+    ) -> StarkConfig<Pcs<Val, Challenge, H, C>, Challenge, Challenger<Val, B>> {
+        let val_mmcs = ValMmcs::<Val, H, C>::new(field_hash, compress);
+        let challenge_mmcs = ChallengeMmcs::<Val, Challenge, H, C>::new(val_mmcs.clone());
+        let dft = Dft::default();
+        let fri_config = FriParameters {
+            log_blowup: fri_config.log_blowup,
+            log_final_poly_len: fri_config.log_final_poly_len,
+            num_queries: fri_config.num_queries,
+            proof_of_work_bits: fri_config.proof_of_work_bits,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs::<Val, Challenge, H, C>::new(dft, val_mmcs, fri_config);
+        let challenger = Challenger::<Val, B>::from_hasher(vec![], byte_hash);
+        StarkConfig::new(pcs, challenger)
+    }
+}
+
+impl<
+    Val: TwoAdicField + PrimeField32 + FieldName,
+    Challenge: TwoAdicField + ExtensionField<Val> + FieldName,
+> Plonky3Config for KeccakMtConfig<Val, Challenge>
+{
+    type StarkGenericConfig = Config<Val, Challenge>;
+    type VerifierConfig = VerifierConfig<Val, Challenge>;
 
-    fn new(trace_height: usize, log_blowup: usize) -> Self
+    fn new(trace_height: usize, pcs: &PcsParams) -> Self
     where
         Self: Sized,
     {
-        let u64_hash = U64Hash::new(KeccakF {});
-        let field_hash = FieldHash::new(u64_hash);
-        let compress = Compress::new(u64_hash);
-        let val_mmcs = ValMmcs::new(field_hash, compress);
-        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-        let dft = Dft::default();
         // TODO: Calculate precise minimum #queries to reach 128-bits provable security.
-        let num_queries = usize::div_ceil(256, log_blowup);
         let fri_config = FriParameters {
-            log_blowup,
-            log_final_poly_len: trace_height.ilog2().saturating_sub(1).min(3) as _,
-            num_queries,
-            proof_of_work_bits: 0,
-            mmcs: challenge_mmcs,
+            log_blowup: pcs.log_inv_rate,
+            log_final_poly_len: pcs
+                .log_final_poly_len
+                .unwrap_or(trace_height.ilog2().saturating_sub(1).min(3) as usize),
+            num_queries: pcs.num_queries(256),
+            proof_of_work_bits: pcs.pow_bits,
+            mmcs: (),
+        };
+        let description = Description {
+            field: Val::NAME.to_string(),
+            extension_degree: Challenge::DEGREE,
+            arithmetization: "AIR".to_string(),
+            pcs: "FRI".to_string(),
+            merkle_hash: Some(format!("Keccak-f[1600] ({VECTOR_LEN} lanes)")),
+            transcript_hash: "Keccak-256".to_string(),
+            log_inv_rate: Some(fri_config.log_blowup),
+            num_queries: Some(fri_config.num_queries),
+            security_bits: None,
+            pow_bits: Some(fri_config.proof_of_work_bits),
         };
-        let pcs = Pcs::new(dft, val_mmcs, fri_config);
-        let byte_hash = ByteHash {};
-        let challenger = Challenger::from_hasher(vec![], byte_hash);
-        let stark_config = StarkConfig::new(pcs, challenger);
-        Self { stark_config }
+        let u64_hash = U64Hash::<KeccakF>::new(KeccakF {});
+        let counted_u64_hash = U64Hash::<CountedKeccakF>::new(CountedKeccakF);
+        Self {
+            stark_config: Self::stark_config_of(
+                &fri_config,
+                FieldHash::new(u64_hash),
+                Compress::new(u64_hash),
+                Keccak256Hash {},
+            ),
+            verifier_config: Self::stark_config_of(
+                &fri_config,
+                CountedHasher::new(SerializingHasher::new(counted_u64_hash)),
+                CountedHasher::new(CompressionFunctionFromHasher::new(counted_u64_hash)),
+                CountedHasher::opaque(Keccak256Hash {}),
+            ),
+            description,
+        }
     }
 
     fn stark_config(&self) -> &Self::StarkGenericConfig {
         &self.stark_config
     }
+
+    fn verifier_config(&self) -> &Self::VerifierConfig {
+        &self.verifier_config
+    }
+
+    fn describe(&self) -> &Description {
+        &self.description
+    }
+}
+
+/// [`KeccakF`] counting permutation calls for [`bench::cost`] in the config of verifiers, with a
+/// vectorized call counted as one per lane.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountedKeccakF;
+
+impl CountedKeccakF {
+    fn count<T>(&self) {
+        cost::count_permutations(size_of::<T>() / size_of::<[u64; 25]>());
+    }
+}
+
+impl<T: Clone> Permutation<T> for CountedKeccakF
+where
+    KeccakF: Permutation<T>,
+{
+    fn permute(&self, input: T) -> T {
+        self.count::<T>();
+        KeccakF {}.permute(input)
+    }
+
+    fn permute_mut(&self, input: &mut T) {
+        self.count::<T>();
+        KeccakF {}.permute_mut(input)
+    }
+}
+
+impl<T: Clone> CryptographicPermutation<T> for CountedKeccakF where
+    KeccakF: CryptographicPermutation<T>
+{
+}
+
+/// Hasher counting calls and bytes hashed for [`bench::cost`] in the config of verifiers.
+///
+/// Permutation calls are counted by [`CountedKeccakF`], or estimated by the message length if the
+/// inner hasher is opaque.
+#[derive(Clone, Copy, Debug)]
+pub struct CountedHasher<H> {
+    inner: H,
+    opaque: bool,
+}
+
+impl<H> CountedHasher<H> {
+    pub fn new(inner: H) -> Self {
+        Self {
+            inner,
+            opaque: false,
+        }
+    }
+
+    pub fn opaque(inner: H) -> Self {
+        Self {
+            inner,
+            opaque: true,
+        }
+    }
+
+    fn count(&self, bytes: usize) {
+        if self.opaque {
+            cost::count_hash_of::<KeccakF1600>(bytes);
+        } else {
+            cost::count_hash(bytes);
+        }
+    }
+}
+
+impl<Item: Clone, Out, H: CryptographicHasher<Item, Out>> CryptographicHasher<Item, Out>
+    for CountedHasher<H>
+{
+    fn hash_iter<I>(&self, input: I) -> Out
+    where
+        I: IntoIterator<Item = Item>,
+    {
+        let mut len = 0;
+        let out = self
+            .inner
+            .hash_iter(input.into_iter().inspect(|_| len += 1));
+        self.count(len * size_of::<Item>());
+        out
+    }
+
+    fn hash_iter_slices<'a, I>(&self, input: I) -> Out
+    where
+        I: IntoIterator<Item = &'a [Item]>,
+        Item: 'a,
+    {
+        let mut len = 0;
+        let out = self
+            .inner
+            .hash_iter_slices(input.into_iter().inspect(|slice| len += slice.len()));
+        self.count(len * size_of::<Item>());
+        out
+    }
+
+    fn hash_slice(&self, input: &[Item]) -> Out {
+        self.count(size_of_val(input));
+        self.inner.hash_slice(input)
+    }
+}
+
+impl<T, const N: usize, C: PseudoCompressionFunction<T, N>> PseudoCompressionFunction<T, N>
+    for CountedHasher<C>
+{
+    fn compress(&self, input: [T; N]) -> T {
+        self.count(size_of::<[T; N]>());
+        self.inner.compress(input)
+    }
 }
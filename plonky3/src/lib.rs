@@ -1,9 +1,11 @@
-use crate::{circuit::Plonky3Circuit, config::Plonky3Config};
-use bench::{HashInSnark, util::pcs_log_inv_rate};
-use p3_uni_stark::{PcsError, Proof, VerificationError, prove, verify};
+use crate::{
+    circuit::{Plonky3Air, Plonky3Circuit},
+    config::Plonky3Config,
+};
+use bench::{HashInSnark, codec::Codec, report::Description, util::pcs_params, workload::Workload};
+use p3_field::Field;
+use p3_uni_stark::{PcsError, Proof, Val, VerificationError, prove, verify};
 use rand::RngCore;
-use tracing_forest::{ForestLayer, util::LevelFilter};
-use tracing_subscriber::{EnvFilter, Registry, prelude::*};
 
 pub mod circuit;
 pub mod config;
@@ -17,18 +19,25 @@ impl<Config, Circuit> HashInSnark for Plonky3<Config, Circuit>
 where
     Config: Plonky3Config,
     Circuit: Plonky3Circuit<Config::StarkGenericConfig>,
+    Circuit::Air: Plonky3Air<Config::VerifierConfig>,
 {
+    type Hash = Circuit::Hash;
     type Input = Circuit::Input;
-    type Proof = Proof<Config::StarkGenericConfig>;
-    type Error = VerificationError<PcsError<Config::StarkGenericConfig>>;
+    /// Proof with the public values it's verified against, serialized only if the circuit has
+    /// any.
+    type Proof = (
+        Proof<Config::VerifierConfig>,
+        Vec<Val<Config::VerifierConfig>>,
+    );
+    type Error = VerificationError<PcsError<Config::VerifierConfig>>;
 
     fn new(num_permutations: usize) -> Self
     where
         Self: Sized,
     {
-        let log_blowup = pcs_log_inv_rate();
-        let circuit = Circuit::new(num_permutations, log_blowup);
-        let config = Config::new(circuit.trace_height(), log_blowup);
+        let pcs = pcs_params();
+        let circuit = Circuit::new(num_permutations, pcs.log_inv_rate);
+        let config = Config::new(circuit.trace_height(), &pcs);
         Self { config, circuit }
     }
 
@@ -36,44 +45,58 @@ where
         self.circuit.num_permutations()
     }
 
+    fn workload(&self) -> Workload {
+        self.circuit.workload()
+    }
+
     fn generate_input(&self, rng: impl RngCore) -> Self::Input {
         self.circuit.generate_input(rng)
     }
 
     fn prove(&self, input: Self::Input) -> Self::Proof {
         let trace = self.circuit.generate_trace(input);
-        prove(
+        let public_values = self.circuit.public_values(&trace);
+        let proof = prove(
             self.config.stark_config(),
             self.circuit.air(),
             trace,
-            &vec![],
-        )
+            &public_values,
+        );
+        // Converted to the config of verifiers, outside of the timed verification.
+        let codec = Codec::Bincode;
+        codec.decode(&codec.encode(&(proof, public_values)))
     }
 
-    fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error> {
+    fn verify(&self, (proof, public_values): &Self::Proof) -> Result<(), Self::Error> {
         verify(
-            self.config.stark_config(),
+            self.config.verifier_config(),
             self.circuit.air(),
             proof,
-            &vec![],
+            public_values,
         )
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(proof).unwrap()
+    fn field_bits() -> u32 {
+        Val::<Config::StarkGenericConfig>::bits() as u32
     }
 
-    fn deserialize_proof(bytes: &[u8]) -> Self::Proof {
-        bincode::deserialize(bytes).unwrap()
+    fn describe(&self) -> Description {
+        self.config.describe().clone()
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        if Circuit::HAS_PUBLIC_VALUES {
+            codec.encode(proof)
+        } else {
+            codec.encode(&proof.0)
+        }
     }
-}
 
-pub fn setup_trace() {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env_lossy();
-    Registry::default()
-        .with(env_filter)
-        .with(ForestLayer::default())
-        .init();
+    fn decode_proof(bytes: &[u8], codec: Codec) -> Self::Proof {
+        if Circuit::HAS_PUBLIC_VALUES {
+            codec.decode(bytes)
+        } else {
+            (codec.decode(bytes), Vec::new())
+        }
+    }
 }
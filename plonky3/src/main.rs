@@ -1,15 +1,26 @@
 use bench::main;
 use bench_plonky3::{
-    circuit::{Blake3Circuit, KeccakCircuit, KoalaBearPoseidon2Circuit},
+    circuit::{
+        Blake3Circuit, Blake3Step, ChainCircuit, KeccakCircuit, KeccakCompression, KeccakStep,
+        KoalaBearPoseidon2Circuit, KoalaBearPoseidon2Compression, KoalaBearPoseidon2Step,
+        MerkleCircuit,
+    },
     config::{BabyBearKeccakMtConfig, KoalaBearKeccakMtConfig},
-    setup_trace, Plonky3,
+    Plonky3,
 };
 
 main!(
-    setup_trace = setup_trace;
-    hash = {
-        Keccak => Plonky3<BabyBearKeccakMtConfig, KeccakCircuit>,
-        Blake3 => Plonky3<BabyBearKeccakMtConfig, Blake3Circuit>,
-        Poseidon2 => Plonky3<KoalaBearKeccakMtConfig, KoalaBearPoseidon2Circuit>,
-    };
+    Keccak@baby_bear_keccak_mt => Plonky3<BabyBearKeccakMtConfig, KeccakCircuit>,
+    Keccak@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, KeccakCircuit>,
+    Blake3@baby_bear_keccak_mt => Plonky3<BabyBearKeccakMtConfig, Blake3Circuit>,
+    Blake3@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, Blake3Circuit>,
+    Poseidon2@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, KoalaBearPoseidon2Circuit>,
+    KeccakMerkle@baby_bear_keccak_mt => Plonky3<BabyBearKeccakMtConfig, MerkleCircuit<KeccakCompression>>,
+    KeccakMerkle@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, MerkleCircuit<KeccakCompression>>,
+    Poseidon2Merkle@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, MerkleCircuit<KoalaBearPoseidon2Compression>>,
+    KeccakChain@baby_bear_keccak_mt => Plonky3<BabyBearKeccakMtConfig, ChainCircuit<KeccakStep>>,
+    KeccakChain@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, ChainCircuit<KeccakStep>>,
+    Blake3Chain@baby_bear_keccak_mt => Plonky3<BabyBearKeccakMtConfig, ChainCircuit<Blake3Step>>,
+    Blake3Chain@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, ChainCircuit<Blake3Step>>,
+    Poseidon2Chain@koala_bear_keccak_mt => Plonky3<KoalaBearKeccakMtConfig, ChainCircuit<KoalaBearPoseidon2Step>>,
 );
@@ -1,19 +1,28 @@
-use bench::{criterion::bench, util::po2};
+use bench::{
+    alloc::TrackingAllocator,
+    criterion::{bench, bench_verify, Metric, PeakHeap, ProofSize},
+    util::{log_permutations, po2},
+};
 use bench_stwo::hash::{StwoBlake2s, StwoPoseidon2};
 use criterion::{criterion_group, criterion_main, Criterion};
 
-fn bench_blake2s(c: &mut Criterion) {
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+fn bench_blake2s<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("blake2s");
 
     type H = StwoBlake2s;
-    bench::<H>(&mut group, "m31_blake2s_mt", po2(10..13));
+    bench::<H, _>(&mut group, "m31_blake2s_mt", po2(log_permutations(10..13)));
+    bench_verify::<H, _>(&mut group, "m31_blake2s_mt", po2(log_permutations(10..13)));
 }
 
-fn bench_m31_poseidon2(c: &mut Criterion) {
+fn bench_m31_poseidon2<M: Metric>(c: &mut Criterion<M>) {
     let mut group = c.benchmark_group("m31_poseidon2");
 
     type H = StwoPoseidon2;
-    bench::<H>(&mut group, "m31_blake2s_mt", po2(15..18));
+    bench::<H, _>(&mut group, "m31_blake2s_mt", po2(log_permutations(15..18)));
+    bench_verify::<H, _>(&mut group, "m31_blake2s_mt", po2(log_permutations(15..18)));
 }
 
 criterion_group!(
@@ -21,4 +30,14 @@ criterion_group!(
     config = Criterion::default().sample_size(10);
     targets = bench_blake2s, bench_m31_poseidon2,
 );
-criterion_main!(benches);
+criterion_group!(
+    name = proof_size;
+    config = Criterion::default().with_measurement(ProofSize).sample_size(10).without_plots();
+    targets = bench_blake2s, bench_m31_poseidon2,
+);
+criterion_group!(
+    name = peak_heap;
+    config = Criterion::default().with_measurement(PeakHeap).sample_size(10).without_plots();
+    targets = bench_blake2s, bench_m31_poseidon2,
+);
+criterion_main!(benches, proof_size, peak_heap);
@@ -1,5 +1,82 @@
+use bench::{cost, hash::Blake2s, report::Description, util::pcs_params};
+use core::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Blake2sChannel,
+    fields::m31::BaseField,
+    fri::FriConfig,
+    pcs::PcsConfig,
+    vcs::{
+        blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
+        ops::MerkleHasher,
+    },
+    MerkleChannel,
+};
+
 mod blake2s;
 mod poseidon2;
 
 pub use blake2s::StwoBlake2s;
 pub use poseidon2::StwoPoseidon2;
+
+/// Returns [`PcsConfig`] of env `PCS_*`, see [`pcs_params`].
+fn pcs_config() -> PcsConfig {
+    let pcs = pcs_params();
+    PcsConfig {
+        pow_bits: pcs.pow_bits as _,
+        fri_config: FriConfig::new(
+            pcs.log_final_poly_len.unwrap_or(0) as _,
+            pcs.log_inv_rate as _,
+            pcs.num_queries(256),
+        ),
+    }
+}
+
+/// Returns [`Description`] of proofs of `config`, committed by Blake2s Merkle trees with a Blake2s
+/// channel.
+fn describe(config: &PcsConfig) -> Description {
+    Description {
+        field: "M31".to_string(),
+        extension_degree: 4,
+        arithmetization: "AIR".to_string(),
+        pcs: "Circle FRI".to_string(),
+        merkle_hash: Some("Blake2s".to_string()),
+        transcript_hash: "Blake2s".to_string(),
+        log_inv_rate: Some(config.fri_config.log_blowup_factor as usize),
+        num_queries: Some(config.fri_config.n_queries),
+        security_bits: None,
+        pow_bits: Some(config.pow_bits as usize),
+    }
+}
+
+/// [`MerkleHasher`] counting node hashes of verifiers for [`bench::cost`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CountedMerkleHasher<H>(PhantomData<H>);
+
+impl<H: MerkleHasher> MerkleHasher for CountedMerkleHasher<H> {
+    type Hash = H::Hash;
+
+    fn hash_node(
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        let bytes =
+            children_hashes.map_or(0, |_| 2 * size_of::<Self::Hash>()) + size_of_val(column_values);
+        cost::count_hash_of::<Blake2s>(bytes);
+        H::hash_node(children_hashes, column_values)
+    }
+}
+
+/// [`Blake2sMerkleChannel`] with Merkle hashes counted by [`CountedMerkleHasher`].
+#[derive(Default)]
+pub struct CountedBlake2sMerkleChannel;
+
+impl MerkleChannel for CountedBlake2sMerkleChannel {
+    type C = Blake2sChannel;
+    type H = CountedMerkleHasher<Blake2sMerkleHasher>;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        Blake2sMerkleChannel::mix_root(channel, root)
+    }
+}
@@ -1,7 +1,7 @@
-use bench::{util::pcs_log_inv_rate, HashInSnark};
+use crate::hash::{describe, pcs_config, CountedBlake2sMerkleChannel};
+use bench::{codec::Codec, hash::Blake2s, report::Description, HashInSnark};
 use rand::RngCore;
 use stwo::core::{
-    fri::FriConfig,
     pcs::PcsConfig,
     vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
     verifier::VerificationError,
@@ -14,22 +14,21 @@ pub struct StwoBlake2s {
 }
 
 impl HashInSnark for StwoBlake2s {
+    type Hash = Blake2s;
     type Input = ();
     type Proof = BlakeProof<Blake2sMerkleHasher>;
     type Error = VerificationError;
 
+    /// `prove_blake` of `stwo_examples` generates its own inputs.
+    const TAKES_INPUT: bool = false;
+
     fn new(num_permutations: usize) -> Self
     where
         Self: Sized,
     {
         let num_permutations = num_permutations.next_power_of_two();
 
-        let log_blowup_factor = pcs_log_inv_rate();
-        let num_queries = usize::div_ceil(256, log_blowup_factor);
-        let config = PcsConfig {
-            pow_bits: 0,
-            fri_config: FriConfig::new(0, log_blowup_factor as _, num_queries),
-        };
+        let config = pcs_config();
 
         Self {
             num_permutations,
@@ -49,16 +48,24 @@ impl HashInSnark for StwoBlake2s {
     }
 
     fn verify(&self, proof: &Self::Proof) -> Result<(), Self::Error> {
-        verify_blake::<Blake2sMerkleChannel>(
+        verify_blake::<CountedBlake2sMerkleChannel>(
             bincode::deserialize(&bincode::serialize(proof).unwrap()).unwrap(),
         )
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(proof).unwrap()
+    fn describe(&self) -> Description {
+        describe(&self.config)
+    }
+
+    fn field_bits() -> u32 {
+        31
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(proof)
     }
 
-    fn deserialize_proof(data: &[u8]) -> Self::Proof {
-        bincode::deserialize(data).unwrap()
+    fn decode_proof(data: &[u8], codec: Codec) -> Self::Proof {
+        codec.decode(data)
     }
 }
@@ -1,13 +1,13 @@
-use bench::{util::pcs_log_inv_rate, HashInSnark};
+use crate::hash::{describe, pcs_config, CountedBlake2sMerkleChannel};
+use bench::{codec::Codec, hash::M31Poseidon2, report::Description, HashInSnark};
 use rand::RngCore;
 use stwo::core::{
     air::Component,
     channel::Blake2sChannel,
     fields::qm31::QM31,
-    fri::FriConfig,
     pcs::{CommitmentSchemeVerifier, PcsConfig, TreeVec},
     proof::StarkProof,
-    vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
+    vcs::blake2_merkle::Blake2sMerkleHasher,
     verifier::{verify, VerificationError},
     ColumnVec,
 };
@@ -22,6 +22,7 @@ pub struct StwoPoseidon2 {
 }
 
 impl HashInSnark for StwoPoseidon2 {
+    type Hash = M31Poseidon2;
     type Input = ();
     type Proof = (
         QM31,
@@ -30,6 +31,9 @@ impl HashInSnark for StwoPoseidon2 {
     );
     type Error = VerificationError;
 
+    /// `prove_poseidon` of `stwo_examples` generates its own inputs.
+    const TAKES_INPUT: bool = false;
+
     fn new(num_permutations: usize) -> Self
     where
         Self: Sized,
@@ -37,12 +41,7 @@ impl HashInSnark for StwoPoseidon2 {
         let num_permutations = (num_permutations >> N_LOG_INSTANCES_PER_ROW).next_power_of_two()
             << N_LOG_INSTANCES_PER_ROW;
 
-        let log_blowup_factor = pcs_log_inv_rate() as _;
-        let num_queries = usize::div_ceil(256, log_blowup_factor);
-        let config = PcsConfig {
-            pow_bits: 0,
-            fri_config: FriConfig::new(0, log_blowup_factor as _, num_queries),
-        };
+        let config = pcs_config();
 
         Self {
             num_permutations,
@@ -69,7 +68,7 @@ impl HashInSnark for StwoPoseidon2 {
     fn verify(&self, (claimed_sum, sizes, proof): &Self::Proof) -> Result<(), Self::Error> {
         let mut channel = Blake2sChannel::default();
         let mut commitment_scheme =
-            CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(self.config);
+            CommitmentSchemeVerifier::<CountedBlake2sMerkleChannel>::new(self.config);
 
         commitment_scheme.commit(proof.commitments[0], &sizes[0], &mut channel);
 
@@ -94,11 +93,19 @@ impl HashInSnark for StwoPoseidon2 {
         verify(&[&component], &mut channel, &mut commitment_scheme, proof)
     }
 
-    fn serialize_proof(proof: &Self::Proof) -> Vec<u8> {
-        bincode::serialize(proof).unwrap()
+    fn describe(&self) -> Description {
+        describe(&self.config)
+    }
+
+    fn field_bits() -> u32 {
+        31
+    }
+
+    fn encode_proof(proof: &Self::Proof, codec: Codec) -> Vec<u8> {
+        codec.encode(proof)
     }
 
-    fn deserialize_proof(data: &[u8]) -> Self::Proof {
-        bincode::deserialize(data).unwrap()
+    fn decode_proof(data: &[u8], codec: Codec) -> Self::Proof {
+        codec.decode(data)
     }
 }